@@ -0,0 +1,403 @@
+//! Static analyses over rule tables
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+use crate::{
+    convert::{final_state_to_empty_stack, Fresh},
+    grammar::{Grammar, Symbol, Triple},
+    Acceptance, AutomataBuilder, Movements, Transition,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictKind {
+    /// Both transitions apply to the same state, input symbol and stack top
+    SameInput,
+    /// The first transition is an epsilon move that can fire instead of the second one
+    EpsilonAndInput,
+}
+
+/// A pair of transitions that can both fire from the same configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict<VocabElement, StackData, Q> {
+    pub kind: ConflictKind,
+    pub first: Transition<VocabElement, StackData, Q>,
+    pub second: Transition<VocabElement, StackData, Q>,
+}
+
+/// Every pair of conflicting transitions in `movements`, in no particular order. See
+/// [`sorted_conflicts`] for a stable order.
+///
+/// Two transitions conflict when they share the state and stack top and either read the
+/// same input (including two epsilon moves) or one of them is an epsilon move. The rule
+/// table is deterministic exactly when there are none.
+pub fn conflicts<VocabElement, StackData, Q>(
+    movements: &Movements<VocabElement, StackData, Q>,
+) -> Vec<Conflict<VocabElement, StackData, Q>>
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    let mut groups = HashMap::<_, HashMap<_, Vec<_>>>::new();
+    for ((from, v, top), to) in movements.iter() {
+        groups
+            .entry((from, top))
+            .or_default()
+            .entry(v)
+            .or_default()
+            .push(to);
+    }
+
+    let mut conflicts = Vec::new();
+    for ((from, top), by_input) in groups {
+        let transition = |v: &Option<VocabElement>, to: &(Q, Vec<StackData>)| {
+            ((from.clone(), v.clone(), top.clone()), to.clone())
+        };
+        for (v, targets) in &by_input {
+            for (i, first) in targets.iter().enumerate() {
+                for second in &targets[i + 1..] {
+                    conflicts.push(Conflict {
+                        kind: ConflictKind::SameInput,
+                        first: transition(v, first),
+                        second: transition(v, second),
+                    });
+                }
+            }
+        }
+        let Some(epsilon) = by_input.get(&&None) else {
+            continue;
+        };
+        for (v, targets) in by_input.iter().filter(|(v, _)| v.is_some()) {
+            for first in epsilon {
+                for second in targets {
+                    conflicts.push(Conflict {
+                        kind: ConflictKind::EpsilonAndInput,
+                        first: transition(&None, first),
+                        second: transition(v, second),
+                    });
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// The [`conflicts`] of `movements`, sorted by their transitions. The two transitions of a
+/// [`ConflictKind::SameInput`] conflict are in order too.
+pub fn sorted_conflicts<VocabElement, StackData, Q>(
+    movements: &Movements<VocabElement, StackData, Q>,
+) -> Vec<Conflict<VocabElement, StackData, Q>>
+where
+    VocabElement: Clone + Hash + Ord,
+    StackData: Clone + Hash + Ord,
+    Q: Clone + Hash + Ord,
+{
+    let mut conflicts = conflicts(movements);
+    for conflict in &mut conflicts {
+        if conflict.kind == ConflictKind::SameInput && conflict.first > conflict.second {
+            std::mem::swap(&mut conflict.first, &mut conflict.second);
+        }
+    }
+    conflicts.sort_by(|a, b| (&a.first, &a.second).cmp(&(&b.first, &b.second)));
+    conflicts
+}
+
+/// Whether at most one transition can fire from every configuration
+pub fn is_deterministic<VocabElement, StackData, Q>(
+    movements: &Movements<VocabElement, StackData, Q>,
+) -> bool
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    movements.iter().all(|((from, v, top), _)| {
+        let successors = |v: Option<VocabElement>| {
+            movements
+                .get(&(from.clone(), v, top.clone()))
+                .map_or(0, HashSet::len)
+        };
+        let epsilon = if v.is_some() { successors(None) } else { 0 };
+        successors(v.clone()) + epsilon <= 1
+    })
+}
+
+/// The parts of an automaton that can take part in an accepting run
+#[derive(Debug, Clone)]
+pub struct Usage<VocabElement, StackData, Q> {
+    /// Whether the automaton accepts no word at all
+    pub empty: bool,
+    /// States that no accepting run goes through
+    pub useless_states: HashSet<Q>,
+    /// Transitions that no accepting run fires
+    pub useless_transitions: HashSet<Transition<VocabElement, StackData, Q>>,
+}
+
+/// Finds the states and transitions of `builder` that can never be used on an accepting
+/// run, according to its acceptance mode.
+///
+/// This goes through [`Grammar::from_automaton`]: a transition is useful exactly when a
+/// production built from it survives [`Grammar::remove_useless`]. The grammar has a
+/// production for every choice of intermediate states, so the cost grows with the number
+/// of states raised to the longest push.
+pub fn usage<VocabElement, StackData, Q>(
+    builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+) -> Usage<VocabElement, StackData, Q>
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    let (empty, useful) = match builder.acceptance {
+        Acceptance::EmptyStack => useful_transitions(builder, None),
+        Acceptance::Both => useful_transitions(builder, Some(&builder.accepting)),
+        Acceptance::FinalState => {
+            let (empty, useful) = useful_transitions(&final_state_to_empty_stack(builder), None);
+            let useful = useful
+                .into_iter()
+                .filter_map(|((from, v, top), (to, push))| {
+                    let push = push
+                        .into_iter()
+                        .map(Fresh::original)
+                        .collect::<Option<_>>()?;
+                    Some((
+                        (from.original()?, v, top.original()?),
+                        (to.original()?, push),
+                    ))
+                })
+                .collect();
+            (empty, useful)
+        }
+    };
+
+    let mut states = builder.movements.states();
+    states.insert(builder.state.clone());
+    states.extend(builder.accepting.iter().cloned());
+    for ((from, _, _), (to, _)) in &useful {
+        states.remove(from);
+        states.remove(to);
+    }
+    if !empty {
+        states.remove(&builder.state);
+    }
+    Usage {
+        empty,
+        useless_states: states,
+        useless_transitions: builder
+            .movements
+            .iter()
+            .filter(|(key, target)| !useful.contains(&((*key).clone(), (*target).clone())))
+            .map(|(key, target)| (key.clone(), target.clone()))
+            .collect(),
+    }
+}
+
+/// Whether the language accepted by empty stack is empty, and the transitions used on
+/// some accepting run. With `accepting`, the stack must also be emptied in one of
+/// those states.
+fn useful_transitions<VocabElement, StackData, Q>(
+    builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+    accepting: Option<&HashSet<Q>>,
+) -> (bool, HashSet<Transition<VocabElement, StackData, Q>>)
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    let mut grammar = Grammar::from_automaton(builder);
+    if let Some(accepting) = accepting {
+        let ends_accepting = |body: &[Symbol<VocabElement, Triple<StackData, Q>>]| {
+            let end = match body.last() {
+                Some(Symbol::Nonterminal(Triple::Pop { to, .. })) => to,
+                _ => &builder.state,
+            };
+            accepting.contains(end)
+        };
+        grammar = Grammar::new(
+            Triple::Start,
+            grammar
+                .productions()
+                .iter()
+                .filter(|p| p.head != Triple::Start || ends_accepting(&p.body))
+                .cloned(),
+        );
+    }
+    let grammar = grammar.remove_useless();
+
+    let mut empty = true;
+    let mut useful = HashSet::new();
+    for production in grammar.productions() {
+        let Triple::Pop { from, symbol, to } = &production.head else {
+            empty = false;
+            continue;
+        };
+        let (v, pops) = match production.body.split_first() {
+            Some((Symbol::Terminal(v), pops)) => (Some(v.clone()), pops),
+            _ => (None, &production.body[..]),
+        };
+        let mut next = to.clone();
+        let mut push = Vec::new();
+        for (i, pop) in pops.iter().enumerate() {
+            if let Symbol::Nonterminal(Triple::Pop { from, symbol, .. }) = pop {
+                if i == 0 {
+                    next = from.clone();
+                }
+                push.push(symbol.clone());
+            }
+        }
+        useful.insert(((from.clone(), v, symbol.clone()), (next, push)));
+    }
+    (empty, useful)
+}
+
+impl<VocabElement, StackData, Q> Display for Conflict<VocabElement, StackData, Q>
+where
+    VocabElement: Display,
+    StackData: Display,
+    Q: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let relation = match self.kind {
+            ConflictKind::SameInput => "conflicts with",
+            ConflictKind::EpsilonAndInput => "can preempt",
+        };
+        write_transition(f, &self.first)?;
+        write!(f, " {relation} ")?;
+        write_transition(f, &self.second)
+    }
+}
+
+fn write_transition<VocabElement, StackData, Q>(
+    f: &mut fmt::Formatter<'_>,
+    ((from, v, top), (to, push)): &Transition<VocabElement, StackData, Q>,
+) -> fmt::Result
+where
+    VocabElement: Display,
+    StackData: Display,
+    Q: Display,
+{
+    match v {
+        Some(v) => write!(f, "{from}, {v}, {top} -> {to}, ")?,
+        None => write!(f, "{from}, ε, {top} -> {to}, ")?,
+    }
+    if push.is_empty() {
+        return write!(f, "ε");
+    }
+    for (i, symbol) in push.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{symbol}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{fixtures::an_bn, Acceptance, AutomataBuilder, Movements};
+
+    use super::{conflicts, is_deterministic, sorted_conflicts, usage, ConflictKind};
+
+    #[test]
+    fn test_deterministic() {
+        let ruleset = an_bn();
+        assert!(is_deterministic(&ruleset));
+        assert!(conflicts(&ruleset).is_empty());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut ruleset = an_bn();
+        ruleset.insert(("q0", Some('a'), 'A'), ("q1", vec!['A']));
+        ruleset.insert(("q1", Some('c'), 'Z'), ("q1", vec!['Z']));
+        assert!(!is_deterministic(&ruleset));
+
+        let conflicts = sorted_conflicts(&ruleset);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].kind, ConflictKind::SameInput);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "q0, a, A -> q0, A A conflicts with q0, a, A -> q1, A"
+        );
+        assert_eq!(conflicts[1].kind, ConflictKind::EpsilonAndInput);
+        assert_eq!(
+            conflicts[1].to_string(),
+            "q1, ε, Z -> q1, ε can preempt q1, c, Z -> q1, Z"
+        );
+    }
+
+    #[test]
+    /// The conflicts of types that only implement `Hash` and `Eq`
+    fn test_conflicts_unordered() {
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum State {
+            Q0,
+            Q1,
+        }
+        use State::*;
+
+        let mut ruleset = Movements::new();
+        ruleset.insert((Q0, Some('a'), 'Z'), (Q0, vec!['Z']));
+        ruleset.insert((Q0, Some('a'), 'Z'), (Q1, vec!['Z']));
+        ruleset.insert((Q1, None, 'Z'), (Q1, vec![]));
+        ruleset.insert((Q1, Some('b'), 'Z'), (Q1, vec!['Z']));
+
+        let conflicts = conflicts(&ruleset);
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts
+            .iter()
+            .any(|c| c.kind == ConflictKind::SameInput && c.first.0 == (Q0, Some('a'), 'Z')));
+        assert!(conflicts
+            .iter()
+            .any(|c| c.kind == ConflictKind::EpsilonAndInput && c.first.0 == (Q1, None, 'Z')));
+    }
+
+    #[test]
+    fn test_usage() {
+        let mut ruleset = an_bn();
+        // q2 can never empty the stack, and q3 is never reached
+        ruleset.insert(("q0", Some('c'), 'Z'), ("q2", vec!['Z']));
+        ruleset.insert(("q2", Some('c'), 'Z'), ("q2", vec!['A', 'Z']));
+        ruleset.insert(("q3", Some('b'), 'A'), ("q1", vec![]));
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], ruleset);
+
+        let usage = usage(&automata_builder);
+        assert!(!usage.empty);
+        assert_eq!(usage.useless_states, HashSet::from(["q2", "q3"]));
+        assert_eq!(
+            usage.useless_transitions,
+            HashSet::from([
+                (("q0", Some('c'), 'Z'), ("q2", vec!['Z'])),
+                (("q2", Some('c'), 'Z'), ("q2", vec!['A', 'Z'])),
+                (("q3", Some('b'), 'A'), ("q1", vec![])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_usage_final_state() {
+        let mut ruleset = an_bn();
+        ruleset.remove(&("q1", None, 'Z'));
+        ruleset.insert(("q1", None, 'Z'), ("q2", vec!['Z']));
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], ruleset)
+            .accepting(Acceptance::FinalState, ["q2"]);
+        let usage = super::usage(&automata_builder);
+        assert!(!usage.empty);
+        assert!(usage.useless_states.is_empty());
+        assert!(usage.useless_transitions.is_empty());
+
+        // Emptying the stack is useless when the run has to end in q2
+        let automata_builder =
+            AutomataBuilder::new("q0", vec!['Z'], an_bn()).accepting(Acceptance::Both, ["q2"]);
+        let usage = super::usage(&automata_builder);
+        assert!(usage.empty);
+        assert_eq!(usage.useless_states, HashSet::from(["q0", "q1", "q2"]));
+        assert_eq!(usage.useless_transitions.len(), 5);
+    }
+}
@@ -0,0 +1,228 @@
+//! Conversions between acceptance by empty stack and acceptance by final state,
+//! using the bottom-marker construction.
+
+use std::hash::Hash;
+
+use crate::{Acceptance, AutomataBuilder, Movements};
+
+/// A state or stack symbol of a converted automaton
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Fresh<T> {
+    Original(T),
+    /// The new initial state, or the bottom marker below the original initial stack
+    Start,
+    /// The new accepting state, or the state that drains the stack
+    End,
+}
+
+impl<T> Fresh<T> {
+    /// The original state or stack symbol, if this is one
+    pub fn original(self) -> Option<T> {
+        match self {
+            Fresh::Original(original) => Some(original),
+            Fresh::Start | Fresh::End => None,
+        }
+    }
+}
+
+pub type Converted<VocabElement, StackData, Q> = AutomataBuilder<
+    Fresh<StackData>,
+    Fresh<Q>,
+    Movements<VocabElement, Fresh<StackData>, Fresh<Q>>,
+>;
+
+/// Builds an automaton accepting by final state the language that `builder` accepts by
+/// empty stack. The acceptance mode of `builder` is ignored.
+///
+/// The bottom marker can only become the top of the stack once the original automaton has
+/// emptied its stack, at which point the new automaton moves to its only accepting state.
+pub fn empty_stack_to_final_state<VocabElement, StackData, Q>(
+    builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+) -> Converted<VocabElement, StackData, Q>
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    let mut movements = start(builder);
+    let mut states = builder.movements.states();
+    states.insert(builder.state.clone());
+    for state in states {
+        movements.insert(
+            (Fresh::Original(state), None, Fresh::Start),
+            (Fresh::End, vec![]),
+        );
+    }
+    AutomataBuilder::new(Fresh::Start, vec![Fresh::Start], movements)
+        .accepting(Acceptance::FinalState, [Fresh::End])
+}
+
+/// Builds an automaton accepting by empty stack the language that `builder` accepts by
+/// final state. The acceptance mode of `builder` is ignored.
+///
+/// The bottom marker keeps the stack from being emptied before an accepting state is
+/// reached, after which the stack is drained without consuming input.
+pub fn final_state_to_empty_stack<VocabElement, StackData, Q>(
+    builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+) -> Converted<VocabElement, StackData, Q>
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    let mut movements = start(builder);
+    let mut symbols = builder.movements.stack_symbols();
+    symbols.extend(builder.stack.0.iter().cloned());
+    let symbols = symbols
+        .into_iter()
+        .map(Fresh::Original)
+        .chain(std::iter::once(Fresh::Start))
+        .collect::<Vec<_>>();
+    for state in &builder.accepting {
+        for symbol in &symbols {
+            movements.insert(
+                (Fresh::Original(state.clone()), None, symbol.clone()),
+                (Fresh::End, vec![]),
+            );
+        }
+    }
+    for symbol in symbols {
+        movements.insert((Fresh::End, None, symbol), (Fresh::End, vec![]));
+    }
+    AutomataBuilder::new(Fresh::Start, vec![Fresh::Start], movements)
+}
+
+/// The original movements, plus a movement from the new initial state that pushes the
+/// original initial stack above the bottom marker
+fn start<VocabElement, StackData, Q>(
+    builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+) -> Movements<VocabElement, Fresh<StackData>, Fresh<Q>>
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    let mut movements: Movements<_, _, _> = builder
+        .movements
+        .iter()
+        .map(|((from, v, top), (to, push))| {
+            (
+                (
+                    Fresh::Original(from.clone()),
+                    v.clone(),
+                    Fresh::Original(top.clone()),
+                ),
+                (
+                    Fresh::Original(to.clone()),
+                    push.iter().cloned().map(Fresh::Original).collect(),
+                ),
+            )
+        })
+        .collect();
+    let push = builder
+        .stack
+        .0
+        .iter()
+        .rev()
+        .cloned()
+        .map(Fresh::Original)
+        .chain(std::iter::once(Fresh::Start))
+        .collect();
+    movements.insert(
+        (Fresh::Start, None, Fresh::Start),
+        (Fresh::Original(builder.state.clone()), push),
+    );
+    movements
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Acceptance, AutomataBuilder, Movements};
+
+    use super::{empty_stack_to_final_state, final_state_to_empty_stack};
+
+    #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+    enum State {
+        Q0,
+        Q1,
+        Q2,
+    }
+
+    use State::*;
+
+    #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+    enum StackElement {
+        A0,
+        A,
+    }
+
+    use StackElement::*;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+    enum Vocab {
+        a,
+        b,
+    }
+    use Vocab::*;
+
+    fn words() -> Vec<Vec<Vocab>> {
+        vec![
+            vec![],
+            vec![a],
+            vec![b],
+            vec![a, b],
+            vec![b, a],
+            vec![a, a, b],
+            vec![a, b, b],
+            vec![a, a, b, b],
+            vec![a, b, a, b],
+            vec![a, a, a, b, b, b],
+        ]
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, converted from empty stack to final state
+    fn test_empty_stack_to_final_state() {
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
+        ruleset.insert((Q0, Some(a), A0), (Q0, vec![A, A0]));
+        ruleset.insert((Q0, Some(a), A), (Q0, vec![A, A]));
+        ruleset.insert((Q0, Some(b), A), (Q1, vec![]));
+        ruleset.insert((Q1, Some(b), A), (Q1, vec![]));
+        ruleset.insert((Q1, None, A0), (Q1, vec![]));
+
+        let original = AutomataBuilder::new(Q0, vec![A0], ruleset);
+        let converted = empty_stack_to_final_state(&original);
+        assert_eq!(converted.acceptance, Acceptance::FinalState);
+        for word in words() {
+            assert_eq!(
+                original.build(word.clone().into_iter()).complete(),
+                converted.build(word.clone().into_iter()).complete(),
+                "{word:?}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, converted from final state to empty stack
+    fn test_final_state_to_empty_stack() {
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
+        ruleset.insert((Q0, Some(a), A0), (Q0, vec![A, A0]));
+        ruleset.insert((Q0, Some(a), A), (Q0, vec![A, A]));
+        ruleset.insert((Q0, Some(b), A), (Q1, vec![]));
+        ruleset.insert((Q1, Some(b), A), (Q1, vec![]));
+        ruleset.insert((Q1, None, A0), (Q2, vec![A0]));
+
+        let original =
+            AutomataBuilder::new(Q0, vec![A0], ruleset).accepting(Acceptance::FinalState, [Q2]);
+        let converted = final_state_to_empty_stack(&original);
+        assert_eq!(converted.acceptance, Acceptance::EmptyStack);
+        for word in words() {
+            assert_eq!(
+                original.build(word.clone().into_iter()).complete(),
+                converted.build(word.clone().into_iter()).complete(),
+                "{word:?}"
+            );
+        }
+    }
+}
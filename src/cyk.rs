@@ -0,0 +1,239 @@
+//! The CYK recognizer for grammars in Chomsky normal form
+
+use crate::grammar::{Grammar, ParseTree, Production, Symbol};
+
+/// How a nonterminal derives a part of the word
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Split<N> {
+    /// By a production whose body is the only terminal of that part
+    Terminal,
+    /// By a production with body `left right`, where `left` derives the first `at` symbols
+    Binary { at: usize, left: N, right: N },
+}
+
+/// A production of the grammar that is not in Chomsky normal form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotChomsky<T, N>(pub Production<T, N>);
+
+/// The filled CYK chart of a word.
+///
+/// Each cell holds the nonterminals deriving a part of the word, given by its start and its
+/// length, along with every way they derive it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chart<T, N> {
+    word: Vec<T>,
+    start: N,
+    /// Whether the start symbol has an empty production
+    empty: bool,
+    /// Indexed by `(length - 1) * word.len() + start`
+    cells: Vec<Vec<(N, Vec<Split<N>>)>>,
+}
+
+impl<T, N> Chart<T, N>
+where
+    T: Clone + Eq,
+    N: Clone + Eq,
+{
+    /// Fills the chart of `word`, for a grammar whose productions are all `A -> a`,
+    /// `A -> B C` or the empty production of the start symbol, such as those built by
+    /// [`Grammar::chomsky`]
+    pub fn new(grammar: &Grammar<T, N>, word: &[T]) -> Result<Self, NotChomsky<T, N>> {
+        let mut terminal = Vec::new();
+        let mut binary = Vec::new();
+        let mut empty = false;
+        for production in grammar.productions() {
+            match &production.body[..] {
+                [Symbol::Terminal(t)] => terminal.push((&production.head, t)),
+                [Symbol::Nonterminal(b), Symbol::Nonterminal(c)] => {
+                    binary.push((&production.head, b, c))
+                }
+                [] if &production.head == grammar.start() => empty = true,
+                _ => return Err(NotChomsky(production.clone())),
+            }
+        }
+
+        let n = word.len();
+        let mut chart = Self {
+            word: word.to_vec(),
+            start: grammar.start().clone(),
+            empty,
+            cells: vec![Vec::new(); n * n],
+        };
+        for (i, symbol) in word.iter().enumerate() {
+            for (head, _) in terminal.iter().filter(|(_, t)| *t == symbol) {
+                chart.add(i, 1, head, Split::Terminal);
+            }
+        }
+        for length in 2..=n {
+            for i in 0..=n - length {
+                for at in 1..length {
+                    for (head, b, c) in &binary {
+                        if chart.derives(i, at, b) && chart.derives(i + at, length - at, c) {
+                            let split = Split::Binary {
+                                at,
+                                left: (*b).clone(),
+                                right: (*c).clone(),
+                            };
+                            chart.add(i, length, head, split);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(chart)
+    }
+
+    fn add(&mut self, start: usize, length: usize, head: &N, split: Split<N>) {
+        let index = self.index(start, length);
+        let cell = &mut self.cells[index];
+        match cell.iter_mut().find(|(n, _)| n == head) {
+            Some((_, splits)) => splits.push(split),
+            None => cell.push((head.clone(), vec![split])),
+        }
+    }
+
+    fn index(&self, start: usize, length: usize) -> usize {
+        assert!(length >= 1 && start + length <= self.word.len());
+        (length - 1) * self.word.len() + start
+    }
+
+    pub fn word(&self) -> &[T] {
+        &self.word
+    }
+
+    /// The nonterminals that derive the `length` symbols of the word from `start` on
+    pub fn nonterminals(&self, start: usize, length: usize) -> impl Iterator<Item = &N> {
+        self.cells[self.index(start, length)].iter().map(|(n, _)| n)
+    }
+
+    /// Every way `nonterminal` derives the `length` symbols of the word from `start` on
+    pub fn splits(&self, start: usize, length: usize, nonterminal: &N) -> &[Split<N>] {
+        self.cells[self.index(start, length)]
+            .iter()
+            .find(|(n, _)| n == nonterminal)
+            .map_or(&[], |(_, splits)| splits)
+    }
+
+    pub fn derives(&self, start: usize, length: usize, nonterminal: &N) -> bool {
+        !self.splits(start, length, nonterminal).is_empty()
+    }
+
+    /// Whether the start symbol derives the whole word
+    pub fn accepted(&self) -> bool {
+        match self.word.len() {
+            0 => self.empty,
+            n => self.derives(0, n, &self.start),
+        }
+    }
+
+    /// A parse tree of the whole word, following the first split of every cell
+    pub fn parse_tree(&self) -> Option<ParseTree<T, N>> {
+        if !self.accepted() {
+            return None;
+        }
+        if self.word.is_empty() {
+            return Some(ParseTree::Node {
+                head: self.start.clone(),
+                children: vec![],
+            });
+        }
+        Some(self.tree(0, self.word.len(), &self.start))
+    }
+
+    fn tree(&self, start: usize, length: usize, head: &N) -> ParseTree<T, N> {
+        let children = match &self.splits(start, length, head)[0] {
+            Split::Terminal => vec![ParseTree::Leaf(self.word[start].clone())],
+            Split::Binary { at, left, right } => vec![
+                self.tree(start, *at, left),
+                self.tree(start + at, length - at, right),
+            ],
+        };
+        ParseTree::Node {
+            head: head.clone(),
+            children,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{n, parentheses, t},
+        grammar::{Grammar, Production},
+    };
+
+    use super::{Chart, NotChomsky, Split};
+
+    #[test]
+    /// Test for the classic S -> AB | BC, A -> BA | a, B -> CC | b, C -> AB | a
+    fn test_chart() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('A'), n('B')]),
+                Production::new('S', [n('B'), n('C')]),
+                Production::new('A', [n('B'), n('A')]),
+                Production::new('A', [t('a')]),
+                Production::new('B', [n('C'), n('C')]),
+                Production::new('B', [t('b')]),
+                Production::new('C', [n('A'), n('B')]),
+                Production::new('C', [t('a')]),
+            ],
+        );
+        let word = "baaba".chars().collect::<Vec<_>>();
+        let chart = Chart::new(&grammar, &word).unwrap();
+        assert!(chart.accepted());
+        assert_eq!(chart.nonterminals(0, 1).collect::<Vec<_>>(), [&'B']);
+        assert_eq!(chart.nonterminals(1, 1).collect::<Vec<_>>(), [&'A', &'C']);
+        assert_eq!(chart.nonterminals(0, 2).collect::<Vec<_>>(), [&'S', &'A']);
+        assert_eq!(chart.nonterminals(1, 3).count(), 1);
+        assert_eq!(
+            chart.nonterminals(0, 5).collect::<Vec<_>>(),
+            [&'S', &'A', &'C']
+        );
+        assert_eq!(
+            chart.splits(0, 2, &'S'),
+            [Split::Binary {
+                at: 1,
+                left: 'B',
+                right: 'C'
+            }]
+        );
+        let tree = chart.parse_tree().unwrap();
+        assert_eq!(tree.leaves(), word.iter().collect::<Vec<_>>());
+
+        let chart = Chart::new(&grammar, &['a', 'b', 'b']).unwrap();
+        assert!(!chart.accepted());
+        assert_eq!(chart.parse_tree(), None);
+        assert!(!Chart::new(&grammar, &[]).unwrap().accepted());
+    }
+
+    #[test]
+    /// Test for balanced parentheses, S -> (S)S | ε, against its automaton
+    fn test_against_automaton() {
+        let grammar = parentheses();
+        assert_eq!(
+            Chart::new(&grammar, &[]),
+            Err(NotChomsky(Production::new(
+                'S',
+                [t('('), n('S'), t(')'), n('S')]
+            )))
+        );
+
+        let cnf = grammar.chomsky().grammar;
+        let automata_builder = grammar.automaton();
+        for word in [
+            "", "()", "(())", "()()", "(()())()", "(", ")(", "(()", "())(",
+        ] {
+            let chart = Chart::new(&cnf, &word.chars().collect::<Vec<_>>()).unwrap();
+            assert_eq!(
+                chart.accepted(),
+                automata_builder.build(word.chars()).complete(),
+                "{word}"
+            );
+            if let Some(tree) = chart.parse_tree() {
+                assert_eq!(tree.leaves().into_iter().collect::<String>(), word);
+            }
+        }
+    }
+}
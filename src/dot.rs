@@ -0,0 +1,151 @@
+//! Graphviz export of state diagrams
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{AutomataBuilder, Movements};
+
+/// Renders the state diagram of `builder` as a DOT digraph.
+///
+/// The initial state gets an incoming arrow and accepting states are drawn with a double
+/// circle. Edges are labelled `a, X / Y Z`, meaning read `a` with `X` on top of the stack
+/// and replace it by `Y Z`, and parallel transitions share a single edge. Nodes are named
+/// by their position among the states sorted by label, so that states which display the
+/// same are still drawn apart. Nodes and labels are sorted, so equal automata render the
+/// same, as long as no two states display the same.
+pub fn to_dot<VocabElement, StackData, Q>(
+    builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+) -> String
+where
+    VocabElement: Display + Hash + Eq,
+    StackData: Display + Hash + Eq,
+    Q: Display + Clone + Hash + Eq,
+{
+    let mut states = builder.movements().states();
+    states.insert(builder.initial_state().clone());
+    states.extend(builder.accepting_states().iter().cloned());
+    let mut states = states
+        .into_iter()
+        .map(|q| (q.to_string(), q))
+        .collect::<Vec<_>>();
+    states.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let ids = states
+        .iter()
+        .enumerate()
+        .map(|(id, (_, q))| (q, id))
+        .collect::<HashMap<_, _>>();
+
+    let mut edges = BTreeMap::<_, Vec<_>>::new();
+    for ((from, v, top), (to, push)) in builder.movements().iter() {
+        let v = v.as_ref().map_or("ε".to_string(), ToString::to_string);
+        let push = match push
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+        {
+            push if push.is_empty() => "ε".to_string(),
+            push => push,
+        };
+        edges
+            .entry((ids[from], ids[to]))
+            .or_default()
+            .push(format!("{v}, {top} / {push}"));
+    }
+
+    let mut dot = String::from("digraph {\n    rankdir=LR;\n    node [shape=circle];\n");
+    dot.push_str("    __start [shape=point];\n");
+    for (id, (label, q)) in states.iter().enumerate() {
+        if builder.accepting_states().contains(q) {
+            dot.push_str(&format!(
+                "    n{id} [label={}, shape=doublecircle];\n",
+                quote(label)
+            ));
+        } else {
+            dot.push_str(&format!("    n{id} [label={}];\n", quote(label)));
+        }
+    }
+    dot.push_str(&format!(
+        "    __start -> n{};\n",
+        ids[builder.initial_state()]
+    ));
+    for ((from, to), mut labels) in edges {
+        labels.sort();
+        dot.push_str(&format!(
+            "    n{from} -> n{to} [label={}];\n",
+            quote(&labels.join("\n"))
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use crate::{fixtures::an_bn, Acceptance, AutomataBuilder, Movements};
+
+    use super::to_dot;
+
+    #[test]
+    fn test_an_bn_dot() {
+        let mut ruleset = an_bn();
+        ruleset.remove(&("q1", None, 'Z'));
+        ruleset.insert(("q1", None, 'Z'), ("q2", vec!['Z']));
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], ruleset)
+            .accepting(Acceptance::FinalState, ["q2"]);
+
+        assert_eq!(
+            to_dot(&automata_builder),
+            r#"digraph {
+    rankdir=LR;
+    node [shape=circle];
+    __start [shape=point];
+    n0 [label="q0"];
+    n1 [label="q1"];
+    n2 [label="q2", shape=doublecircle];
+    __start -> n0;
+    n0 -> n0 [label="a, A / A A\na, Z / A Z"];
+    n0 -> n1 [label="b, A / ε"];
+    n1 -> n1 [label="b, A / ε"];
+    n1 -> n2 [label="ε, Z / Z"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_states_displayed_the_same() {
+        #[derive(Clone, PartialEq, Eq, Hash)]
+        struct Twin(u8);
+
+        impl fmt::Display for Twin {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "q")
+            }
+        }
+
+        let mut ruleset = Movements::new();
+        ruleset.insert((Twin(0), Some('a'), 'Z'), (Twin(1), vec!['Z']));
+        let dot = to_dot(&AutomataBuilder::new(Twin(0), vec!['Z'], ruleset));
+
+        assert!(dot.contains("    n0 [label=\"q\"];\n    n1 [label=\"q\"];\n"));
+        assert!(
+            dot.contains("    __start -> n0;\n    n0 -> n1 [label=\"a, Z / Z\"];\n")
+                || dot.contains("    __start -> n1;\n    n1 -> n0 [label=\"a, Z / Z\"];\n"),
+            "{dot}"
+        );
+    }
+}
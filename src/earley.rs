@@ -0,0 +1,433 @@
+//! The Earley parser, for any context-free grammar, building a shared packed parse forest
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::grammar::{Grammar, ParseTree, Production, Symbol};
+
+/// A child of a packed alternative
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Child<T> {
+    Leaf(T),
+    /// The index of a node in the forest
+    Node(usize),
+}
+
+/// One way of deriving the span of a node, by a production whose body matches the children
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packed<T, N> {
+    pub production: Production<T, N>,
+    pub children: Vec<Child<T>>,
+}
+
+/// A nonterminal deriving the symbols of the word from `start` up to `end`, in every way it
+/// does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForestNode<T, N> {
+    pub nonterminal: N,
+    pub start: usize,
+    pub end: usize,
+    pub alternatives: Vec<Packed<T, N>>,
+}
+
+/// Every parse of a word, with each nonterminal and span shared by all the parses that go
+/// through it.
+///
+/// Grammars with cycles of unit or empty productions give words infinitely many parses,
+/// in which case a node can be among its own descendants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest<T, N> {
+    nodes: Vec<ForestNode<T, N>>,
+    root: Option<usize>,
+}
+
+/// A production with a dot before its `dot`-th symbol, started at `origin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+
+/// Parses `word`, returning the forest of its parses from the start symbol.
+///
+/// Runs in cubic time in the length of the word, or better for unambiguous grammars, but
+/// the forest can take longer to build for very ambiguous ones.
+pub fn parse<T, N>(grammar: &Grammar<T, N>, word: &[T]) -> Forest<T, N>
+where
+    T: Clone + Eq,
+    N: Clone + Hash + Eq,
+{
+    let productions = grammar.productions();
+    let nullable = grammar.nullable();
+    let n = word.len();
+    let mut sets: Vec<Vec<Item>> = vec![Vec::new(); n + 1];
+    let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); n + 1];
+    let mut add = |sets: &mut Vec<Vec<Item>>, k: usize, item: Item| {
+        if seen[k].insert(item) {
+            sets[k].push(item);
+        }
+    };
+    for (production, p) in productions.iter().enumerate() {
+        if &p.head == grammar.start() {
+            add(
+                &mut sets,
+                0,
+                Item {
+                    production,
+                    dot: 0,
+                    origin: 0,
+                },
+            );
+        }
+    }
+
+    // Where each nonterminal ends when it starts at a given position
+    let mut ends: HashMap<(N, usize), Vec<usize>> = HashMap::new();
+    for k in 0..=n {
+        let mut i = 0;
+        while i < sets[k].len() {
+            let item = sets[k][i];
+            let production = &productions[item.production];
+            match production.body.get(item.dot) {
+                // Predict
+                Some(Symbol::Nonterminal(next)) => {
+                    for (index, p) in productions.iter().enumerate() {
+                        if &p.head == next {
+                            add(
+                                &mut sets,
+                                k,
+                                Item {
+                                    production: index,
+                                    dot: 0,
+                                    origin: k,
+                                },
+                            );
+                        }
+                    }
+                    if nullable.contains(next) {
+                        add(
+                            &mut sets,
+                            k,
+                            Item {
+                                dot: item.dot + 1,
+                                ..item
+                            },
+                        );
+                    }
+                }
+                // Scan
+                Some(Symbol::Terminal(t)) => {
+                    if k < n && &word[k] == t {
+                        add(
+                            &mut sets,
+                            k + 1,
+                            Item {
+                                dot: item.dot + 1,
+                                ..item
+                            },
+                        );
+                    }
+                }
+                // Complete
+                None => {
+                    let spans = ends
+                        .entry((production.head.clone(), item.origin))
+                        .or_default();
+                    if !spans.contains(&k) {
+                        spans.push(k);
+                    }
+                    let waiting = sets[item.origin]
+                        .iter()
+                        .filter(|waiting| {
+                            productions[waiting.production].body.get(waiting.dot)
+                                == Some(&Symbol::Nonterminal(production.head.clone()))
+                        })
+                        .copied()
+                        .collect::<Vec<_>>();
+                    for waiting in waiting {
+                        add(
+                            &mut sets,
+                            k,
+                            Item {
+                                dot: waiting.dot + 1,
+                                ..waiting
+                            },
+                        );
+                    }
+                }
+            }
+            i += 1;
+        }
+    }
+
+    let mut builder = ForestBuilder {
+        productions,
+        word,
+        ends,
+        ids: HashMap::new(),
+        nodes: Vec::new(),
+    };
+    let root = builder
+        .ends
+        .get(&(grammar.start().clone(), 0))
+        .is_some_and(|ends| ends.contains(&n))
+        .then(|| builder.node(grammar.start(), 0, n));
+    Forest {
+        nodes: builder.nodes,
+        root,
+    }
+}
+
+/// A terminal, or a nonterminal with the span it derives, before it gets its node
+#[derive(Clone)]
+enum Part<T, N> {
+    Leaf(T),
+    Span(N, usize, usize),
+}
+
+struct ForestBuilder<'a, T, N> {
+    productions: &'a [Production<T, N>],
+    word: &'a [T],
+    ends: HashMap<(N, usize), Vec<usize>>,
+    ids: HashMap<(N, usize, usize), usize>,
+    nodes: Vec<ForestNode<T, N>>,
+}
+
+impl<T, N> ForestBuilder<'_, T, N>
+where
+    T: Clone + Eq,
+    N: Clone + Hash + Eq,
+{
+    /// The node for `nonterminal` deriving the word from `start` up to `end`, which the
+    /// recognizer must have found
+    fn node(&mut self, nonterminal: &N, start: usize, end: usize) -> usize {
+        let key = (nonterminal.clone(), start, end);
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.ids.insert(key, id);
+        self.nodes.push(ForestNode {
+            nonterminal: nonterminal.clone(),
+            start,
+            end,
+            alternatives: Vec::new(),
+        });
+        let productions = self.productions;
+        for production in productions.iter().filter(|p| &p.head == nonterminal) {
+            for children in self.children(&production.body, start, end) {
+                let children = children
+                    .into_iter()
+                    .map(|child| match child {
+                        Part::Leaf(t) => Child::Leaf(t),
+                        Part::Span(n, start, end) => Child::Node(self.node(&n, start, end)),
+                    })
+                    .collect();
+                self.nodes[id].alternatives.push(Packed {
+                    production: production.clone(),
+                    children,
+                });
+            }
+        }
+        id
+    }
+
+    /// Every way of matching `body` against the word from `start` up to `end`
+    fn children(&self, body: &[Symbol<T, N>], start: usize, end: usize) -> Vec<Vec<Part<T, N>>> {
+        let Some((first, rest)) = body.split_first() else {
+            return if start == end { vec![vec![]] } else { vec![] };
+        };
+        let matches = match first {
+            Symbol::Terminal(t) if start < end && &self.word[start] == t => {
+                vec![(Part::Leaf(t.clone()), start + 1)]
+            }
+            Symbol::Terminal(_) => vec![],
+            Symbol::Nonterminal(n) => self
+                .ends
+                .get(&(n.clone(), start))
+                .into_iter()
+                .flatten()
+                .filter(|&&next| next <= end)
+                .map(|&next| (Part::Span(n.clone(), start, next), next))
+                .collect(),
+        };
+        let mut children = Vec::new();
+        for (child, next) in matches {
+            for mut rest in self.children(rest, next, end) {
+                rest.insert(0, child.clone());
+                children.push(rest);
+            }
+        }
+        children
+    }
+}
+
+impl<T, N> Forest<T, N> {
+    pub fn accepted(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// The node of the start symbol deriving the whole word, if it does
+    pub fn root(&self) -> Option<usize> {
+        self.root
+    }
+
+    pub fn node(&self, id: usize) -> &ForestNode<T, N> {
+        &self.nodes[id]
+    }
+
+    pub fn nodes(&self) -> &[ForestNode<T, N>] {
+        &self.nodes
+    }
+
+    /// Whether the word has more than one parse
+    pub fn ambiguous(&self) -> bool {
+        self.nodes.iter().any(|node| node.alternatives.len() > 1)
+    }
+
+    /// One of the parse trees, taking the first alternative of every node that does not
+    /// lead back to one of its ancestors
+    pub fn parse_tree(&self) -> Option<ParseTree<T, N>>
+    where
+        T: Clone,
+        N: Clone,
+    {
+        self.tree(self.root?, &mut Vec::new())
+    }
+
+    fn tree(&self, id: usize, ancestors: &mut Vec<usize>) -> Option<ParseTree<T, N>>
+    where
+        T: Clone,
+        N: Clone,
+    {
+        if ancestors.contains(&id) {
+            return None;
+        }
+        ancestors.push(id);
+        let node = &self.nodes[id];
+        let tree = node.alternatives.iter().find_map(|packed| {
+            let children = packed
+                .children
+                .iter()
+                .map(|child| match child {
+                    Child::Leaf(t) => Some(ParseTree::Leaf(t.clone())),
+                    Child::Node(id) => self.tree(*id, ancestors),
+                })
+                .collect::<Option<_>>()?;
+            Some(ParseTree::Node {
+                head: node.nonterminal.clone(),
+                children,
+            })
+        });
+        ancestors.pop();
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{n, t},
+        grammar::{Grammar, Production},
+    };
+
+    use super::{parse, Child};
+
+    fn chars(word: &str) -> Vec<char> {
+        word.chars().collect()
+    }
+
+    #[test]
+    /// Test for sums, E -> E+E | n, which is ambiguous and left recursive
+    fn test_ambiguous_sums() {
+        let grammar = Grammar::new(
+            'E',
+            [
+                Production::new('E', [n('E'), t('+'), n('E')]),
+                Production::new('E', [t('n')]),
+            ],
+        );
+        let forest = parse(&grammar, &chars("n+n+n"));
+        assert!(forest.accepted());
+        assert!(forest.ambiguous());
+        let root = forest.node(forest.root().unwrap());
+        assert_eq!((root.nonterminal, root.start, root.end), ('E', 0, 5));
+        assert_eq!(root.alternatives.len(), 2);
+        // The parses share the nodes of each n and of the middle n+n
+        assert_eq!(forest.nodes().len(), 6);
+        let spans = root
+            .alternatives
+            .iter()
+            .map(|packed| match packed.children[..] {
+                [Child::Node(left), Child::Leaf('+'), Child::Node(right)] => (
+                    forest.node(left).end - forest.node(left).start,
+                    forest.node(right).end - forest.node(right).start,
+                ),
+                _ => panic!("{packed:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert!(spans.contains(&(1, 3)) && spans.contains(&(3, 1)));
+        let tree = forest.parse_tree().unwrap();
+        assert_eq!(tree.leaves().into_iter().collect::<String>(), "n+n+n");
+
+        let forest = parse(&grammar, &chars("n+n"));
+        assert!(!forest.ambiguous());
+        assert_eq!(forest.parse_tree().unwrap().to_string(), "E(E(n) + E(n))");
+        assert!(!parse(&grammar, &chars("n+")).accepted());
+        assert!(!parse(&grammar, &[]).accepted());
+    }
+
+    #[test]
+    /// Test for (a^n)(b^m)(c^n) where n, m >= 0, against its automaton
+    fn test_against_automaton() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [t('a'), n('S'), t('c')]),
+                Production::new('S', [n('B')]),
+                Production::new('B', [n('B'), t('b')]),
+                Production::new('B', [n('E')]),
+                Production::new('E', []),
+            ],
+        );
+        let automata_builder = grammar
+            .automaton()
+            .budget(crate::Budget::unlimited().max_stack_depth(12));
+        for word in [
+            "", "b", "ac", "abc", "aacc", "abbbc", "aabcc", "a", "c", "aac", "acb", "bac", "abcc",
+        ] {
+            let forest = parse(&grammar, &chars(word));
+            assert_eq!(
+                forest.accepted(),
+                automata_builder.build(word.chars()).complete(),
+                "{word}"
+            );
+            if let Some(tree) = forest.parse_tree() {
+                assert_eq!(tree.leaves().into_iter().collect::<String>(), word);
+            }
+        }
+    }
+
+    #[test]
+    /// Test for S -> S | a, whose parses go around a cycle
+    fn test_cycle() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('S')]),
+                Production::new('S', [t('a')]),
+            ],
+        );
+        let forest = parse(&grammar, &['a']);
+        let root = forest.root().unwrap();
+        assert_eq!(forest.nodes().len(), 1);
+        assert_eq!(
+            forest.node(root).alternatives[0].children,
+            [Child::Node(root)]
+        );
+        assert_eq!(forest.parse_tree().unwrap().to_string(), "S(a)");
+    }
+}
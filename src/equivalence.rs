@@ -0,0 +1,167 @@
+//! Bounded comparison of the languages of two automata.
+//!
+//! Equivalence of pushdown automata is undecidable, so these functions only compare both
+//! automata on finitely many words. Finding no counterexample is no proof of equivalence.
+
+use std::hash::Hash;
+
+use crate::{trace::Trace, words::Shortlex, AutomataBuilder, Movement};
+
+/// A word that one automaton accepts and the other does not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample<VocabElement, LeftData, LeftQ, RightData, RightQ> {
+    pub word: Vec<VocabElement>,
+    pub left: Trace<VocabElement, LeftData, LeftQ>,
+    pub right: Trace<VocabElement, RightData, RightQ>,
+}
+
+/// Runs both automata on every word over `alphabet` up to `max_length` symbols long, in
+/// shortlex order, and returns the first word on which they disagree.
+///
+/// A run that diverges or exceeds its [`crate::Budget`] counts as a rejection, and its
+/// trace says so.
+pub fn compare_up_to<VocabElement, LeftData, LeftQ, LeftM, RightData, RightQ, RightM, A>(
+    left: &AutomataBuilder<LeftData, LeftQ, LeftM>,
+    right: &AutomataBuilder<RightData, RightQ, RightM>,
+    alphabet: A,
+    max_length: usize,
+) -> Option<Counterexample<VocabElement, LeftData, LeftQ, RightData, RightQ>>
+where
+    VocabElement: Clone,
+    LeftData: Clone + Hash + Eq,
+    LeftQ: Clone + Hash + Eq,
+    LeftM: Clone + Movement<VocabElement, LeftData, LeftQ>,
+    RightData: Clone + Hash + Eq,
+    RightQ: Clone + Hash + Eq,
+    RightM: Clone + Movement<VocabElement, RightData, RightQ>,
+    A: IntoIterator<Item = VocabElement>,
+{
+    Shortlex::new(alphabet)
+        .max_length(max_length)
+        .find_map(|word| compare_on(left, right, word))
+}
+
+/// Runs both automata on `samples` random words over `alphabet`, each up to `max_length`
+/// symbols long, and returns the first word on which they disagree.
+///
+/// Lengths and symbols are drawn uniformly from a generator seeded with `seed`, so the
+/// same seed always checks the same words.
+pub fn compare_sample<VocabElement, LeftData, LeftQ, LeftM, RightData, RightQ, RightM, A>(
+    left: &AutomataBuilder<LeftData, LeftQ, LeftM>,
+    right: &AutomataBuilder<RightData, RightQ, RightM>,
+    alphabet: A,
+    max_length: usize,
+    samples: usize,
+    seed: u64,
+) -> Option<Counterexample<VocabElement, LeftData, LeftQ, RightData, RightQ>>
+where
+    VocabElement: Clone,
+    LeftData: Clone + Hash + Eq,
+    LeftQ: Clone + Hash + Eq,
+    LeftM: Clone + Movement<VocabElement, LeftData, LeftQ>,
+    RightData: Clone + Hash + Eq,
+    RightQ: Clone + Hash + Eq,
+    RightM: Clone + Movement<VocabElement, RightData, RightQ>,
+    A: IntoIterator<Item = VocabElement>,
+{
+    let alphabet = alphabet.into_iter().collect::<Vec<_>>();
+    let mut random = SplitMix64(seed);
+    (0..samples).find_map(|_| {
+        let length = if alphabet.is_empty() {
+            0
+        } else {
+            random.below(max_length + 1)
+        };
+        let word = (0..length)
+            .map(|_| alphabet[random.below(alphabet.len())].clone())
+            .collect();
+        compare_on(left, right, word)
+    })
+}
+
+fn compare_on<VocabElement, LeftData, LeftQ, LeftM, RightData, RightQ, RightM>(
+    left: &AutomataBuilder<LeftData, LeftQ, LeftM>,
+    right: &AutomataBuilder<RightData, RightQ, RightM>,
+    word: Vec<VocabElement>,
+) -> Option<Counterexample<VocabElement, LeftData, LeftQ, RightData, RightQ>>
+where
+    VocabElement: Clone,
+    LeftData: Clone + Hash + Eq,
+    LeftQ: Clone + Hash + Eq,
+    LeftM: Clone + Movement<VocabElement, LeftData, LeftQ>,
+    RightData: Clone + Hash + Eq,
+    RightQ: Clone + Hash + Eq,
+    RightM: Clone + Movement<VocabElement, RightData, RightQ>,
+{
+    let left = left.build(word.iter().cloned()).trace();
+    let right = right.build(word.iter().cloned()).trace();
+    (left.accepted() != right.accepted()).then_some(Counterexample { word, left, right })
+}
+
+/// The SplitMix64 generator, good enough to pick test words
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, `n` must not be 0
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{an_bn, n, t},
+        grammar::{Grammar, Production},
+        AutomataBuilder, AutomataResult,
+    };
+
+    use super::{compare_sample, compare_up_to};
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, against the grammar S -> aSb | ab
+    fn test_equivalent() {
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], an_bn());
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [t('a'), n('S'), t('b')]),
+                Production::new('S', [t('a'), t('b')]),
+            ],
+        );
+        let grammar_builder = grammar.automaton();
+        assert_eq!(
+            compare_up_to(&automata_builder, &grammar_builder, ['a', 'b'], 6),
+            None
+        );
+        assert_eq!(
+            compare_sample(&automata_builder, &grammar_builder, ['a', 'b'], 10, 100, 7),
+            None
+        );
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, against (a^n)(b^m) where n >= m >= 1
+    fn test_counterexample() {
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], an_bn());
+        let mut ruleset = an_bn();
+        ruleset.insert(("q1", None, 'A'), ("q1", vec![]));
+        let relaxed = AutomataBuilder::new("q0", vec!['Z'], ruleset);
+
+        let counterexample = compare_up_to(&automata_builder, &relaxed, ['a', 'b'], 6).unwrap();
+        assert_eq!(counterexample.word, ['a', 'a', 'b']);
+        assert_eq!(counterexample.left.result(), AutomataResult::NotAccepting);
+        assert!(counterexample.right.accepted());
+
+        let counterexample = compare_sample(&automata_builder, &relaxed, ['a', 'b'], 8, 1000, 1);
+        assert!(counterexample.is_some());
+    }
+}
@@ -0,0 +1,80 @@
+//! Automata and grammars shared by the tests of several modules
+
+use crate::{
+    grammar::{Grammar, Production, Symbol},
+    Movements,
+};
+
+pub(crate) fn t(c: char) -> Symbol<char, char> {
+    Symbol::Terminal(c)
+}
+
+pub(crate) fn n(c: char) -> Symbol<char, char> {
+    Symbol::Nonterminal(c)
+}
+
+/// (a^n)(b^n) where n >= 1, accepting by empty stack from `q0` with `Z` on the stack
+pub(crate) fn an_bn() -> Movements<char, char, &'static str> {
+    let mut ruleset = Movements::new();
+    ruleset.insert(("q0", Some('a'), 'Z'), ("q0", vec!['A', 'Z']));
+    ruleset.insert(("q0", Some('a'), 'A'), ("q0", vec!['A', 'A']));
+    ruleset.insert(("q0", Some('b'), 'A'), ("q1", vec![]));
+    ruleset.insert(("q1", Some('b'), 'A'), ("q1", vec![]));
+    ruleset.insert(("q1", None, 'Z'), ("q1", vec![]));
+    ruleset
+}
+
+/// E -> E+T | T, T -> T*F | F, F -> (E) | n
+pub(crate) fn expressions() -> Grammar<char, char> {
+    Grammar::new(
+        'E',
+        [
+            Production::new('E', [n('E'), t('+'), n('T')]),
+            Production::new('E', [n('T')]),
+            Production::new('T', [n('T'), t('*'), n('F')]),
+            Production::new('T', [n('F')]),
+            Production::new('F', [t('('), n('E'), t(')')]),
+            Production::new('F', [t('n')]),
+        ],
+    )
+}
+
+/// E -> TA, A -> +TA | ε, T -> FB, B -> *FB | ε, F -> (E) | n, which is [`expressions`]
+/// without left recursion
+pub(crate) fn expressions_ll1() -> Grammar<char, char> {
+    Grammar::new(
+        'E',
+        [
+            Production::new('E', [n('T'), n('A')]),
+            Production::new('A', [t('+'), n('T'), n('A')]),
+            Production::new('A', []),
+            Production::new('T', [n('F'), n('B')]),
+            Production::new('B', [t('*'), n('F'), n('B')]),
+            Production::new('B', []),
+            Production::new('F', [t('('), n('E'), t(')')]),
+            Production::new('F', [t('n')]),
+        ],
+    )
+}
+
+/// Balanced parentheses, S -> (S)S | ε
+pub(crate) fn parentheses() -> Grammar<char, char> {
+    Grammar::new(
+        'S',
+        [
+            Production::new('S', [t('('), n('S'), t(')'), n('S')]),
+            Production::new('S', []),
+        ],
+    )
+}
+
+/// Sums, E -> E+n | n, which is left recursive
+pub(crate) fn sums() -> Grammar<char, char> {
+    Grammar::new(
+        'E',
+        [
+            Production::new('E', [n('E'), t('+'), t('n')]),
+            Production::new('E', [t('n')]),
+        ],
+    )
+}
@@ -0,0 +1,448 @@
+//! Plain-text automaton definitions.
+//!
+//! ```text
+//! # a^n b^n, n >= 1
+//! states: q0 q1
+//! alphabet: a b
+//! stack alphabet: A Z
+//! initial: q0
+//! stack: Z
+//! q0, a, Z -> q0, A Z
+//! q0, a, A -> q0, A A
+//! q0, b, A -> q1, ε
+//! q1, b, A -> q1, ε
+//! q1, ε, Z -> q1, ε
+//! ```
+//!
+//! Input symbols are single characters, while states and stack symbols are words.
+//! Stacks are written top first, and `ε` (or `eps`) stands for no input or an empty stack.
+//! The acceptance mode defaults to empty stack, and can be changed with
+//! `acceptance: empty`, `acceptance: final` or `acceptance: both` along with
+//! `accepting: q1 q2`.
+//!
+//! Only `initial` is required. When `states`, `alphabet` or `stack alphabet` are declared,
+//! every state or symbol used elsewhere must be one of the declared ones.
+
+use std::{collections::BTreeSet, error::Error, fmt};
+
+use crate::{Acceptance, AutomataBuilder, Movements};
+
+pub type TextAutomata = AutomataBuilder<String, String, Movements<char, String, String>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// A slice of a line, which knows where in the text it is
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    line: &'a str,
+    line_number: usize,
+}
+
+impl<'a> Token<'a> {
+    /// A token for `text`, which must be a slice of the same line as `self`
+    fn slice(&self, text: &'a str) -> Self {
+        Self { text, ..*self }
+    }
+
+    fn column(&self) -> usize {
+        let offset = self.text.as_ptr() as usize - self.line.as_ptr() as usize;
+        self.line[..offset].chars().count() + 1
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            line: self.line_number,
+            column: self.column(),
+            message,
+        }
+    }
+
+    /// Splits at whitespace, leaving out `ε`
+    fn symbols(&self) -> Vec<Self> {
+        self.text
+            .split_whitespace()
+            .filter(|token| !is_epsilon(token))
+            .map(|token| self.slice(token))
+            .collect()
+    }
+
+    /// Splits into `N` comma separated, non-empty fields
+    fn fields<const N: usize>(&self) -> Result<[Self; N], ParseError> {
+        let fields = self
+            .text
+            .split(',')
+            .map(|field| self.slice(field.trim()))
+            .collect::<Vec<_>>();
+        let count = fields.len();
+        match <[Self; N]>::try_from(fields) {
+            Ok(fields) if !fields.iter().any(|field| field.text.is_empty()) => Ok(fields),
+            _ => {
+                let text = self.text.trim();
+                let message = format!("expected {N} comma separated fields in `{text}`");
+                Err(self.slice(text).error(format!("{message}, found {count}")))
+            }
+        }
+    }
+
+    /// Checks that the token is a single word
+    fn single(self) -> Result<Self, ParseError> {
+        if self.text.split_whitespace().count() == 1 {
+            Ok(self)
+        } else {
+            Err(self.error(format!("expected a single name, found `{}`", self.text)))
+        }
+    }
+}
+
+struct Transition<'a> {
+    from: Token<'a>,
+    v: Option<Token<'a>>,
+    top: Token<'a>,
+    to: Token<'a>,
+    push: Vec<Token<'a>>,
+}
+
+#[derive(Default)]
+struct Declarations<'a> {
+    states: Option<Vec<Token<'a>>>,
+    alphabet: Option<Vec<Token<'a>>>,
+    stack_alphabet: Option<Vec<Token<'a>>>,
+    initial: Option<Token<'a>>,
+    stack: Vec<Token<'a>>,
+    acceptance: Acceptance,
+    accepting: Vec<Token<'a>>,
+}
+
+pub fn parse(text: &str) -> Result<TextAutomata, ParseError> {
+    let mut declarations = Declarations::default();
+    let mut transitions = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = Token {
+            text: line,
+            line,
+            line_number: i + 1,
+        };
+        let content = line.text.split('#').next().unwrap_or_default().trim();
+        if content.is_empty() {
+            continue;
+        }
+        if let Some((lhs, rhs)) = content.split_once("->") {
+            let [from, v, top] = line.slice(lhs).fields()?;
+            let [to, push] = line.slice(rhs).fields()?;
+            let v = if is_epsilon(v.text) {
+                None
+            } else if v.text.chars().count() == 1 {
+                Some(v)
+            } else {
+                return Err(v.error(format!("input symbol `{}` is not a character", v.text)));
+            };
+            transitions.push(Transition {
+                from: from.single()?,
+                v,
+                top: top.single()?,
+                to: to.single()?,
+                push: push.symbols(),
+            });
+        } else if let Some((key, value)) = content.split_once(':') {
+            let key = line.slice(key.trim());
+            let value = line.slice(value.trim());
+            match key.text {
+                "states" => declarations.states = Some(value.symbols()),
+                "alphabet" => declarations.alphabet = Some(value.symbols()),
+                "stack alphabet" => declarations.stack_alphabet = Some(value.symbols()),
+                "initial" => declarations.initial = Some(value.single()?),
+                "stack" => declarations.stack = value.symbols(),
+                "accepting" => declarations.accepting = value.symbols(),
+                "acceptance" => {
+                    declarations.acceptance = match value.text {
+                        "empty" => Acceptance::EmptyStack,
+                        "final" => Acceptance::FinalState,
+                        "both" => Acceptance::Both,
+                        _ => {
+                            let message = format!("unknown acceptance mode `{}`", value.text);
+                            return Err(value.error(message));
+                        }
+                    }
+                }
+                _ => return Err(key.error(format!("unknown declaration `{}`", key.text))),
+            }
+        } else {
+            let message = "expected a declaration or a transition".to_string();
+            return Err(line.slice(content).error(message));
+        }
+    }
+
+    let Declarations {
+        states,
+        alphabet,
+        stack_alphabet,
+        initial,
+        stack,
+        acceptance,
+        accepting,
+    } = declarations;
+    let initial = initial.ok_or(ParseError {
+        line: text.lines().count().max(1),
+        column: 1,
+        message: "missing initial state".to_string(),
+    })?;
+    let state = |token: Token| declared(token, &states, "state");
+    let input = |token: Token| declared(token, &alphabet, "input symbol");
+    let symbol = |token: Token| declared(token, &stack_alphabet, "stack symbol");
+
+    let mut movements = Movements::new();
+    for transition in transitions {
+        let v = match transition.v {
+            Some(v) => input(v)?.chars().next(),
+            None => None,
+        };
+        let push = transition
+            .push
+            .into_iter()
+            .map(symbol)
+            .collect::<Result<_, _>>()?;
+        movements.insert(
+            (state(transition.from)?, v, symbol(transition.top)?),
+            (state(transition.to)?, push),
+        );
+    }
+    let stack = stack
+        .into_iter()
+        .rev()
+        .map(symbol)
+        .collect::<Result<Vec<_>, _>>()?;
+    let accepting = accepting
+        .into_iter()
+        .map(state)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(AutomataBuilder::new(state(initial)?, stack, movements).accepting(acceptance, accepting))
+}
+
+/// Prints `automata_builder` in the format read by [`parse`], declaring every state and
+/// symbol it uses. States, symbols and transitions are sorted, with epsilon movements
+/// first, so equal automata print the same.
+pub fn print(automata_builder: &TextAutomata) -> String {
+    let movements = automata_builder.movements();
+    let mut states = movements.states().into_iter().collect::<BTreeSet<_>>();
+    states.insert(automata_builder.initial_state().clone());
+    states.extend(automata_builder.accepting_states().iter().cloned());
+    let alphabet = movements
+        .iter()
+        .filter_map(|((_, v, _), _)| *v)
+        .collect::<BTreeSet<_>>();
+    let mut stack_alphabet = movements
+        .stack_symbols()
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    stack_alphabet.extend(automata_builder.initial_stack().iter().cloned());
+    let accepting = automata_builder
+        .accepting_states()
+        .iter()
+        .collect::<BTreeSet<_>>();
+    let mut transitions = movements.iter().collect::<Vec<_>>();
+    transitions.sort();
+
+    let mut text = String::new();
+    let mut declare = |key: &str, value: String| {
+        text.push_str(key);
+        text.push(':');
+        if !value.is_empty() {
+            text.push(' ');
+            text.push_str(&value);
+        }
+        text.push('\n');
+    };
+    declare("states", join(&states));
+    declare("alphabet", join(&alphabet));
+    declare("stack alphabet", join(&stack_alphabet));
+    declare("initial", automata_builder.initial_state().clone());
+    declare("stack", join(automata_builder.initial_stack().iter()));
+    let acceptance = match automata_builder.acceptance() {
+        Acceptance::EmptyStack => "empty",
+        Acceptance::FinalState => "final",
+        Acceptance::Both => "both",
+    };
+    declare("acceptance", acceptance.to_string());
+    if !accepting.is_empty() {
+        declare("accepting", join(accepting));
+    }
+
+    if !transitions.is_empty() {
+        text.push('\n');
+    }
+    for ((from, v, top), (to, push)) in transitions {
+        let v = v.map_or("ε".to_string(), |v| v.to_string());
+        let push = match join(push) {
+            push if push.is_empty() => "ε".to_string(),
+            push => push,
+        };
+        text.push_str(&format!("{from}, {v}, {top} -> {to}, {push}\n"));
+    }
+    text
+}
+
+fn join<I>(symbols: I) -> String
+where
+    I: IntoIterator,
+    I::Item: ToString,
+{
+    symbols
+        .into_iter()
+        .map(|symbol| symbol.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn declared(
+    token: Token,
+    declaration: &Option<Vec<Token>>,
+    kind: &str,
+) -> Result<String, ParseError> {
+    match declaration {
+        Some(declared) if !declared.iter().any(|d| d.text == token.text) => {
+            Err(token.error(format!("undeclared {kind} `{}`", token.text)))
+        }
+        _ => Ok(token.text.to_string()),
+    }
+}
+
+fn is_epsilon(token: &str) -> bool {
+    token == "ε" || token == "eps"
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Acceptance, Stack};
+
+    use super::{parse, print};
+
+    #[test]
+    fn test_parse_an_bn() {
+        let automata_builder = parse(
+            "# a^n b^n, n >= 1
+            initial: q0
+            stack: Z
+            q0, a, Z -> q0, A Z
+            q0, a, A -> q0, A A
+            q0, b, A -> q1, ε
+            q1, b, A -> q1, eps
+            q1, ε, Z -> q1, ε # accept
+            ",
+        )
+        .unwrap();
+        assert_eq!(automata_builder.initial_state(), "q0");
+        assert_eq!(
+            automata_builder.initial_stack(),
+            &Stack::new(vec!["Z".to_string()])
+        );
+        assert_eq!(automata_builder.movements().len(), 5);
+        assert!(automata_builder.build("aabb".chars()).complete());
+        assert!(!automata_builder.build("aab".chars()).complete());
+    }
+
+    #[test]
+    fn test_parse_final_state() {
+        let automata_builder = parse(
+            "initial: p
+            stack: A Z
+            acceptance: final
+            accepting: r
+            p, a, A -> r, A",
+        )
+        .unwrap();
+        assert_eq!(automata_builder.acceptance(), Acceptance::FinalState);
+        assert_eq!(
+            automata_builder.initial_stack(),
+            &Stack::new(vec!["Z".to_string(), "A".to_string()])
+        );
+        assert!(automata_builder.build("a".chars()).complete());
+        assert!(!automata_builder.build("".chars()).complete());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("initial: q0\nq0, ab, Z -> q0, Z").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 5));
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 5: input symbol `ab` is not a character"
+        );
+        let error = parse("initial: q0\n  q0, a -> q0, Z").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        let error = parse("initial: q0\nq0, a, Z -> q 1, Z").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 13));
+        let error = parse("\nfinal q0").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = parse("initial: q0\nacceptance: maybe").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 13));
+        assert_eq!(
+            parse("stack: Z").unwrap_err().message,
+            "missing initial state"
+        );
+    }
+
+    #[test]
+    fn test_parse_undeclared() {
+        let declarations = "states: q0 q1\nalphabet: a\nstack alphabet: Z A\ninitial: q0\n";
+        let error = parse(&format!("{declarations}q0, a, Z -> q2, A Z")).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 13));
+        assert_eq!(error.message, "undeclared state `q2`");
+        let error = parse(&format!("{declarations}q0, b, Z -> q1, A Z")).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 5));
+        let error = parse(&format!("{declarations}q0, a, Z -> q1, A B Z")).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 19));
+        assert_eq!(error.message, "undeclared stack symbol `B`");
+        let error = parse(&format!("{declarations}stack: B")).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 8));
+        assert!(parse(&format!("{declarations}q0, a, Z -> q1, A Z")).is_ok());
+    }
+
+    #[test]
+    fn test_print_round_trip() {
+        let text = "states: p q r
+alphabet: a b
+stack alphabet: A Z
+initial: p
+stack: A Z
+acceptance: both
+accepting: q r
+
+p, a, A -> p, A A
+p, a, Z -> p, A Z
+p, b, A -> q, ε
+q, ε, Z -> r, ε
+q, b, A -> q, ε
+";
+        let automata_builder = parse(text).unwrap();
+        assert_eq!(print(&automata_builder), text);
+
+        let reparsed = parse(&print(&automata_builder)).unwrap();
+        assert_eq!(reparsed.initial_state(), automata_builder.initial_state());
+        assert_eq!(reparsed.initial_stack(), automata_builder.initial_stack());
+        assert_eq!(reparsed.movements(), automata_builder.movements());
+        assert_eq!(reparsed.acceptance(), automata_builder.acceptance());
+        assert_eq!(
+            reparsed.accepting_states(),
+            automata_builder.accepting_states()
+        );
+    }
+}
@@ -0,0 +1,764 @@
+//! Context-free grammars and their translation into pushdown automata
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    hash::Hash,
+};
+
+use crate::{trace::Trace, AutomataBuilder, Budget, Movements};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Symbol<T, N> {
+    Terminal(T),
+    Nonterminal(N),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Production<T, N> {
+    pub head: N,
+    pub body: Vec<Symbol<T, N>>,
+}
+
+impl<T, N> Production<T, N> {
+    pub fn new<B>(head: N, body: B) -> Self
+    where
+        B: Into<Vec<Symbol<T, N>>>,
+    {
+        Self {
+            head,
+            body: body.into(),
+        }
+    }
+}
+
+/// A parse tree, whose inner nodes are nonterminals with one child per symbol in the body
+/// of the production applied to them
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseTree<T, N> {
+    Leaf(T),
+    Node {
+        head: N,
+        children: Vec<ParseTree<T, N>>,
+    },
+}
+
+/// A context-free grammar.
+///
+/// Terminals and nonterminals are kept in the order they are first mentioned, and
+/// productions in the order they were added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar<T, N> {
+    terminals: Vec<T>,
+    nonterminals: Vec<N>,
+    productions: Vec<Production<T, N>>,
+    start: N,
+}
+
+/// An input symbol as seen by a parser, which also sees where the input ends
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Lookahead<T> {
+    Terminal(T),
+    End,
+}
+
+impl<T> Lookahead<T> {
+    /// The symbols of `word` followed by [`Lookahead::End`]
+    pub fn terminate<W>(word: W) -> impl Iterator<Item = Self>
+    where
+        W: IntoIterator<Item = T>,
+    {
+        word.into_iter()
+            .map(Lookahead::Terminal)
+            .chain(std::iter::once(Lookahead::End))
+    }
+}
+
+impl<T: Display> Display for Lookahead<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lookahead::Terminal(t) => write!(f, "{t}"),
+            Lookahead::End => write!(f, "$"),
+        }
+    }
+}
+
+/// A nonterminal of the grammar built from an automaton
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Triple<StackData, Q> {
+    Start,
+    /// `[from, symbol, to]`, deriving the words that take the automaton from state `from`
+    /// to state `to` while popping `symbol` off the stack
+    Pop {
+        from: Q,
+        symbol: StackData,
+        to: Q,
+    },
+}
+
+pub type GrammarAutomata<T, N> = AutomataBuilder<Symbol<T, N>, (), Movements<T, Symbol<T, N>, ()>>;
+
+impl<T, N> Grammar<T, N>
+where
+    T: Eq + Clone,
+    N: Eq + Clone,
+{
+    /// Creates a grammar, taking its terminals and nonterminals from `start` and the
+    /// given productions
+    pub fn new<P>(start: N, productions: P) -> Self
+    where
+        P: IntoIterator<Item = Production<T, N>>,
+    {
+        let mut grammar = Self {
+            terminals: Vec::new(),
+            nonterminals: vec![start.clone()],
+            productions: Vec::new(),
+            start,
+        };
+        for production in productions {
+            grammar.add_production(production);
+        }
+        grammar
+    }
+
+    pub fn add_terminal(&mut self, terminal: T) {
+        if !self.terminals.contains(&terminal) {
+            self.terminals.push(terminal);
+        }
+    }
+
+    pub fn add_nonterminal(&mut self, nonterminal: N) {
+        if !self.nonterminals.contains(&nonterminal) {
+            self.nonterminals.push(nonterminal);
+        }
+    }
+
+    /// Adds a production, unless the grammar already has it
+    pub fn add_production(&mut self, production: Production<T, N>) {
+        if self.productions.contains(&production) {
+            return;
+        }
+        self.add_nonterminal(production.head.clone());
+        for symbol in &production.body {
+            match symbol {
+                Symbol::Terminal(t) => self.add_terminal(t.clone()),
+                Symbol::Nonterminal(n) => self.add_nonterminal(n.clone()),
+            }
+        }
+        self.productions.push(production);
+    }
+}
+
+impl<T, N> Grammar<T, N>
+where
+    T: Eq + Clone,
+    N: Eq + Clone + Hash,
+{
+    /// Nonterminals that derive some word of terminals
+    pub fn generating(&self) -> HashSet<N> {
+        let mut generating = HashSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.productions {
+                if !generating.contains(&production.head)
+                    && production.body.iter().all(|symbol| match symbol {
+                        Symbol::Terminal(_) => true,
+                        Symbol::Nonterminal(n) => generating.contains(n),
+                    })
+                {
+                    generating.insert(production.head.clone());
+                    changed = true;
+                }
+            }
+        }
+        generating
+    }
+
+    /// Nonterminals that derive the empty word
+    pub fn nullable(&self) -> HashSet<N> {
+        let mut nullable = HashSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in self.productions() {
+                if !nullable.contains(&production.head)
+                    && production.body.iter().all(|symbol| match symbol {
+                        Symbol::Terminal(_) => false,
+                        Symbol::Nonterminal(n) => nullable.contains(n),
+                    })
+                {
+                    nullable.insert(production.head.clone());
+                    changed = true;
+                }
+            }
+        }
+        nullable
+    }
+
+    /// Nonterminals that appear in some sentential form derived from the start symbol
+    pub fn reachable(&self) -> HashSet<N> {
+        let mut reachable = HashSet::from([self.start.clone()]);
+        let mut pending = vec![self.start.clone()];
+        while let Some(head) = pending.pop() {
+            for production in self.productions_of(&head) {
+                for symbol in &production.body {
+                    if let Symbol::Nonterminal(n) = symbol {
+                        if reachable.insert(n.clone()) {
+                            pending.push(n.clone());
+                        }
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Removes the nonterminals that derive no word, then the symbols that cannot be reached
+    /// from the start symbol, along with every production that mentions them. The start
+    /// symbol is always kept, even when the language is empty.
+    pub fn remove_useless(&self) -> Self {
+        let generating = self.generating();
+        let generating = Self::new(
+            self.start.clone(),
+            self.productions
+                .iter()
+                .filter(|p| {
+                    generating.contains(&p.head)
+                        && p.body.iter().all(|symbol| match symbol {
+                            Symbol::Terminal(_) => true,
+                            Symbol::Nonterminal(n) => generating.contains(n),
+                        })
+                })
+                .cloned(),
+        );
+        let reachable = generating.reachable();
+        Self::new(
+            self.start.clone(),
+            generating
+                .productions
+                .into_iter()
+                .filter(|p| reachable.contains(&p.head)),
+        )
+    }
+}
+
+impl<T, N> Grammar<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Hash + Eq,
+{
+    /// The terminals that can start a word derived from each nonterminal
+    pub fn first(&self) -> HashMap<N, HashSet<T>> {
+        let nullable = self.nullable();
+        let mut first: HashMap<N, HashSet<T>> = self
+            .nonterminals
+            .iter()
+            .map(|n| (n.clone(), HashSet::new()))
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.productions {
+                let (body, _) = first_of(&first, &nullable, &production.body);
+                let head = first.entry(production.head.clone()).or_default();
+                for t in body {
+                    changed |= head.insert(t);
+                }
+            }
+        }
+        first
+    }
+
+    /// The terminals that can come right after each nonterminal in a sentential form derived
+    /// from the start symbol, along with [`Lookahead::End`] if the input can end there
+    pub fn follow(&self) -> HashMap<N, HashSet<Lookahead<T>>> {
+        let nullable = self.nullable();
+        let first = self.first();
+        let mut follow: HashMap<N, HashSet<Lookahead<T>>> = self
+            .nonterminals
+            .iter()
+            .map(|n| (n.clone(), HashSet::new()))
+            .collect();
+        follow.insert(self.start.clone(), HashSet::from([Lookahead::End]));
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.productions {
+                for (i, symbol) in production.body.iter().enumerate() {
+                    let Symbol::Nonterminal(n) = symbol else {
+                        continue;
+                    };
+                    let (rest, rest_nullable) =
+                        first_of(&first, &nullable, &production.body[i + 1..]);
+                    let mut new = rest
+                        .into_iter()
+                        .map(Lookahead::Terminal)
+                        .collect::<Vec<_>>();
+                    if rest_nullable {
+                        new.extend(follow[&production.head].iter().cloned());
+                    }
+                    let entry = follow.entry(n.clone()).or_default();
+                    for lookahead in new {
+                        changed |= entry.insert(lookahead);
+                    }
+                }
+            }
+        }
+        follow
+    }
+}
+
+/// The terminals that can start a word derived from `symbols`, and whether they derive the
+/// empty word
+pub(crate) fn first_of<T, N>(
+    first: &HashMap<N, HashSet<T>>,
+    nullable: &HashSet<N>,
+    symbols: &[Symbol<T, N>],
+) -> (HashSet<T>, bool)
+where
+    T: Clone + Hash + Eq,
+    N: Hash + Eq,
+{
+    let mut terminals = HashSet::new();
+    for symbol in symbols {
+        match symbol {
+            Symbol::Terminal(t) => {
+                terminals.insert(t.clone());
+                return (terminals, false);
+            }
+            Symbol::Nonterminal(n) => {
+                terminals.extend(first.get(n).into_iter().flatten().cloned());
+                if !nullable.contains(n) {
+                    return (terminals, false);
+                }
+            }
+        }
+    }
+    (terminals, true)
+}
+
+impl<VocabElement, StackData, Q> Grammar<VocabElement, Triple<StackData, Q>>
+where
+    VocabElement: Clone + Hash + Eq,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+{
+    /// Builds a grammar for the language that `builder` accepts by empty stack, using the
+    /// `[p, X, q]` triple construction. The acceptance mode of `builder` is ignored.
+    ///
+    /// Every combination of intermediate states gets its own production, so the result is
+    /// usually full of useless symbols, see [`Grammar::remove_useless`].
+    pub fn from_automaton(
+        builder: &AutomataBuilder<StackData, Q, Movements<VocabElement, StackData, Q>>,
+    ) -> Self {
+        let mut states = builder.movements.states();
+        states.insert(builder.state.clone());
+        let states = states.into_iter().collect::<Vec<_>>();
+
+        let initial_stack = builder.stack.0.iter().rev().cloned().collect::<Vec<_>>();
+        let mut grammar = Self::new(Triple::Start, []);
+        for (body, _) in pops(&builder.state, &initial_stack, &states) {
+            grammar.add_production(Production::new(Triple::Start, body));
+        }
+        for ((from, v, symbol), (next, push)) in builder.movements.iter() {
+            for (pops, to) in pops(next, push, &states) {
+                let body = v.iter().cloned().map(Symbol::Terminal).chain(pops);
+                grammar.add_production(Production::new(
+                    Triple::Pop {
+                        from: from.clone(),
+                        symbol: symbol.clone(),
+                        to,
+                    },
+                    body.collect::<Vec<_>>(),
+                ));
+            }
+        }
+        grammar
+    }
+}
+
+/// Every way of popping `symbols`, top first, starting at `from`, as the body of
+/// nonterminals that does it along with the state it ends in
+#[allow(clippy::type_complexity)]
+fn pops<VocabElement, StackData, Q>(
+    from: &Q,
+    symbols: &[StackData],
+    states: &[Q],
+) -> Vec<(Vec<Symbol<VocabElement, Triple<StackData, Q>>>, Q)>
+where
+    VocabElement: Clone,
+    StackData: Clone,
+    Q: Clone,
+{
+    let Some((symbol, rest)) = symbols.split_first() else {
+        return vec![(vec![], from.clone())];
+    };
+    states
+        .iter()
+        .flat_map(|to| {
+            let pop = Symbol::Nonterminal(Triple::Pop {
+                from: from.clone(),
+                symbol: symbol.clone(),
+                to: to.clone(),
+            });
+            pops(to, rest, states)
+                .into_iter()
+                .map(move |(body, end)| (std::iter::once(pop.clone()).chain(body).collect(), end))
+        })
+        .collect()
+}
+
+impl<T, N> Grammar<T, N> {
+    pub fn terminals(&self) -> &[T] {
+        &self.terminals
+    }
+
+    pub fn nonterminals(&self) -> &[N] {
+        &self.nonterminals
+    }
+
+    pub fn productions(&self) -> &[Production<T, N>] {
+        &self.productions
+    }
+
+    pub fn start(&self) -> &N {
+        &self.start
+    }
+
+    /// Productions with `head` on their left hand side
+    pub fn productions_of<'a>(&'a self, head: &'a N) -> impl Iterator<Item = &'a Production<T, N>>
+    where
+        N: Eq,
+    {
+        self.productions.iter().filter(move |p| &p.head == head)
+    }
+
+    /// Builds the one-state expand/match automaton for the grammar, accepting by empty stack.
+    ///
+    /// The stack starts with the start symbol. A nonterminal on top of the stack is expanded
+    /// into the body of one of its productions without consuming input, and a terminal on
+    /// top of the stack is popped when it matches the next input symbol.
+    ///
+    /// Left recursive grammars expand forever on some branches, as the stack keeps growing,
+    /// until it reaches the stack depth of the [`crate::Budget`].
+    pub fn automaton(&self) -> GrammarAutomata<T, N>
+    where
+        T: Clone + Hash + Eq,
+        N: Clone + Hash + Eq,
+    {
+        let mut movements = Movements::new();
+        for production in &self.productions {
+            movements.insert(
+                ((), None, Symbol::Nonterminal(production.head.clone())),
+                ((), production.body.clone()),
+            );
+        }
+        for terminal in &self.terminals {
+            movements.insert(
+                (
+                    (),
+                    Some(terminal.clone()),
+                    Symbol::Terminal(terminal.clone()),
+                ),
+                ((), vec![]),
+            );
+        }
+        AutomataBuilder::new((), vec![Symbol::Nonterminal(self.start.clone())], movements)
+    }
+}
+
+impl<T, N> Grammar<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Hash + Eq,
+{
+    /// Parses `word` by running [`Grammar::automaton`] on it, returning the parse tree of
+    /// the first derivation found, or `None` if the word is not in the language.
+    ///
+    /// The stack depth is bounded by [`Grammar::parse_depth`], so this terminates on left
+    /// recursive grammars too.
+    pub fn parse<W>(&self, word: W) -> Option<ParseTree<T, N>>
+    where
+        W: IntoIterator<Item = T>,
+    {
+        let word = word.into_iter().collect::<Vec<_>>();
+        let budget = Budget::unlimited().max_stack_depth(self.parse_depth(word.len()));
+        let trace = self
+            .automaton()
+            .budget(budget)
+            .build(word.into_iter())
+            .trace();
+        ParseTree::from_derivation(self.start.clone(), &derivation(&trace)?)
+    }
+
+    /// A stack depth within which [`Grammar::automaton`] accepts every word of length `len`
+    /// in the language.
+    ///
+    /// A derivation tree with the fewest nodes never repeats a nonterminal with the same
+    /// yield along a path, and the yields along a path are nested, so a path has at most
+    /// `(len + 1) * nonterminals` nonterminals. The stack holds the unexpanded siblings
+    /// of the path, at most the longest body minus one for each of them, and the symbol
+    /// being expanded.
+    pub fn parse_depth(&self, len: usize) -> usize {
+        let longest = self
+            .productions
+            .iter()
+            .map(|production| production.body.len())
+            .max()
+            .unwrap_or(0);
+        (len + 1) * self.nonterminals.len() * longest.saturating_sub(1) + 1
+    }
+}
+
+/// The leftmost derivation followed by an accepting run of [`Grammar::automaton`], as the
+/// productions applied in order, or `None` if the run did not accept.
+///
+/// The expanded nonterminal is always on top of the stack, so it is the leftmost one in
+/// the sentential form, and every expansion movement applies one production.
+pub fn derivation<T, N>(trace: &Trace<T, Symbol<T, N>, ()>) -> Option<Vec<Production<T, N>>>
+where
+    T: Clone,
+    N: Clone,
+{
+    if !trace.accepted() {
+        return None;
+    }
+    let productions = trace
+        .steps()
+        .iter()
+        .filter_map(|step| match step.movement()? {
+            (((), None, Symbol::Nonterminal(head)), ((), body)) => {
+                Some(Production::new(head.clone(), body.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    Some(productions)
+}
+
+impl<T, N> ParseTree<T, N> {
+    /// Builds the tree of a leftmost derivation from `start`, or `None` if the productions
+    /// do not form one
+    pub fn from_derivation(start: N, derivation: &[Production<T, N>]) -> Option<Self>
+    where
+        T: Clone,
+        N: Clone + Eq,
+    {
+        let mut productions = derivation.iter();
+        let tree = Self::expand(start, &mut productions)?;
+        productions.next().is_none().then_some(tree)
+    }
+
+    fn expand<'a, I>(head: N, productions: &mut I) -> Option<Self>
+    where
+        T: Clone + 'a,
+        N: Clone + Eq + 'a,
+        I: Iterator<Item = &'a Production<T, N>>,
+    {
+        let production = productions.next().filter(|p| p.head == head)?;
+        let children = production
+            .body
+            .iter()
+            .map(|symbol| match symbol {
+                Symbol::Terminal(t) => Some(ParseTree::Leaf(t.clone())),
+                Symbol::Nonterminal(n) => Self::expand(n.clone(), productions),
+            })
+            .collect::<Option<_>>()?;
+        Some(ParseTree::Node { head, children })
+    }
+
+    /// The terminals at the leaves, from left to right
+    pub fn leaves(&self) -> Vec<&T> {
+        match self {
+            ParseTree::Leaf(t) => vec![t],
+            ParseTree::Node { children, .. } => children.iter().flat_map(Self::leaves).collect(),
+        }
+    }
+}
+
+/// Writes the tree in bracketed form, such as `S(a S(ε) b)`
+impl<T, N> Display for ParseTree<T, N>
+where
+    T: Display,
+    N: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseTree::Leaf(t) => write!(f, "{t}"),
+            ParseTree::Node { head, children } => {
+                write!(f, "{head}(")?;
+                if children.is_empty() {
+                    write!(f, "ε")?;
+                }
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{child}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{n, parentheses, sums, t},
+        AutomataBuilder, Budget, Movements,
+    };
+
+    use super::{derivation, Grammar, ParseTree, Production, Triple};
+
+    #[test]
+    /// Test for balanced parentheses, S -> (S)S | ε
+    fn test_balanced_parentheses() {
+        let grammar = parentheses();
+        assert_eq!(grammar.terminals(), ['(', ')']);
+        assert_eq!(grammar.nonterminals(), ['S']);
+
+        let automata_builder = grammar.automaton();
+        for word in ["", "()", "(())", "()()", "(()())()"] {
+            assert!(automata_builder.build(word.chars()).complete(), "{word}");
+        }
+        for word in ["(", ")", ")(", "(()", "())("] {
+            assert!(!automata_builder.build(word.chars()).complete(), "{word}");
+        }
+    }
+
+    #[test]
+    /// Test for (a^n)(b^m)(c^n) where n, m >= 0
+    fn test_an_bm_cn() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [t('a'), n('S'), t('c')]),
+                Production::new('S', [n('B')]),
+                Production::new('B', [t('b'), n('B')]),
+                Production::new('B', []),
+            ],
+        );
+
+        let automata_builder = grammar.automaton();
+        for word in ["", "b", "ac", "abc", "aacc", "abbbc", "aabcc"] {
+            assert!(automata_builder.build(word.chars()).complete(), "{word}");
+        }
+        for word in ["a", "c", "aac", "acb", "bac", "abcc"] {
+            assert!(!automata_builder.build(word.chars()).complete(), "{word}");
+        }
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, going through the triple construction
+    fn test_an_bn_from_automaton() {
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum State {
+            Q0,
+            Q1,
+        }
+
+        use State::*;
+
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum StackElement {
+            A0,
+            A,
+        }
+
+        use StackElement::*;
+
+        let mut ruleset: Movements<char, StackElement, State> = Movements::new();
+        ruleset.insert((Q0, Some('a'), A0), (Q0, vec![A, A0]));
+        ruleset.insert((Q0, Some('a'), A), (Q0, vec![A, A]));
+        ruleset.insert((Q0, Some('b'), A), (Q1, vec![]));
+        ruleset.insert((Q1, Some('b'), A), (Q1, vec![]));
+        ruleset.insert((Q1, None, A0), (Q1, vec![]));
+        let original = AutomataBuilder::new(Q0, vec![A0], ruleset);
+
+        let grammar = Grammar::from_automaton(&original);
+        let reduced = grammar.remove_useless();
+        assert!(reduced.productions().len() < grammar.productions().len());
+        assert_eq!(reduced.productions().len(), 6);
+        assert!(reduced.generating().contains(&Triple::Start));
+
+        let automata_builder = reduced.automaton();
+        for word in [
+            "", "a", "b", "ab", "ba", "aab", "abb", "aabb", "abab", "aaabbb",
+        ] {
+            assert_eq!(
+                original.build(word.chars()).complete(),
+                automata_builder.build(word.chars()).complete(),
+                "{word}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_remove_useless() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [t('a'), n('A')]),
+                Production::new('S', [t('b')]),
+                Production::new('A', [n('A'), t('a')]),
+                Production::new('B', [t('b')]),
+            ],
+        );
+        let reduced = grammar.remove_useless();
+        assert_eq!(reduced.nonterminals(), ['S']);
+        assert_eq!(reduced.terminals(), ['b']);
+        assert_eq!(reduced.productions(), [Production::new('S', [t('b')])]);
+    }
+
+    #[test]
+    /// Test for balanced parentheses, S -> (S)S | ε, parsed into a tree
+    fn test_parse_tree() {
+        let grammar = parentheses();
+        let tree = grammar.parse("(())".chars()).unwrap();
+        assert_eq!(tree.to_string(), "S(( S(( S(ε) ) S(ε)) ) S(ε))");
+        assert_eq!(tree.leaves(), [&'(', &'(', &')', &')']);
+        assert_eq!(grammar.parse("(()".chars()), None);
+        assert_eq!(
+            grammar.parse("".chars()),
+            Some(ParseTree::Node {
+                head: 'S',
+                children: vec![]
+            })
+        );
+    }
+
+    #[test]
+    /// Test for sums, E -> E+n | n, which is left recursive
+    fn test_left_recursive_derivation() {
+        let grammar = sums();
+        let trace = grammar
+            .automaton()
+            .budget(Budget::unlimited().max_stack_depth(8))
+            .build("n+n".chars())
+            .trace();
+        let derivation = derivation(&trace).unwrap();
+        assert_eq!(
+            derivation,
+            [
+                Production::new('E', [n('E'), t('+'), t('n')]),
+                Production::new('E', [t('n')]),
+            ]
+        );
+        let tree = ParseTree::from_derivation('E', &derivation).unwrap();
+        assert_eq!(tree.to_string(), "E(E(n) + n)");
+        assert_eq!(
+            ParseTree::from_derivation('E', &derivation[1..]),
+            Some(ParseTree::Node {
+                head: 'E',
+                children: vec![ParseTree::Leaf('n')]
+            })
+        );
+        assert_eq!(ParseTree::from_derivation('E', &derivation[..1]), None);
+
+        assert_eq!(grammar.parse("n+n".chars()), Some(tree));
+        assert_eq!(grammar.parse("n+".chars()), None);
+        assert_eq!(grammar.parse("+n+n".chars()), None);
+    }
+}
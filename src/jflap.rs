@@ -0,0 +1,567 @@
+//! Reading and writing pushdown automata in JFLAP's `.jff` format.
+//!
+//! JFLAP stack symbols are single characters, so the stack symbols of a [`TextAutomata`]
+//! read from a file are one character long, and push strings are split into one symbol
+//! per character. JFLAP automata start with `Z` on the stack, and transitions that pop
+//! nothing are expanded into one transition per stack symbol. Unlike in JFLAP, such
+//! transitions cannot fire once the stack is empty.
+//!
+//! Transitions that read or pop more than one symbol go through new intermediate states,
+//! reading first and popping last, so that they take one step per symbol.
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    error::Error,
+    fmt,
+};
+
+use crate::{format::TextAutomata, Acceptance, AutomataBuilder, Movements};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JflapError {
+    pub message: String,
+}
+
+impl JflapError {
+    fn new<M: Into<String>>(message: M) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for JflapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for JflapError {}
+
+const BOTTOM: &str = "Z";
+
+/// Reads a JFLAP pushdown automaton. States are named after their `name` attribute, and
+/// the automaton accepts by final state.
+pub fn from_jff(text: &str) -> Result<TextAutomata, JflapError> {
+    let root = xml::parse(text)?;
+    if root.name != "structure" {
+        return Err(JflapError::new(format!(
+            "expected a <structure> element, found <{}>",
+            root.name
+        )));
+    }
+    match root.child("type").map(xml::Element::text) {
+        Some(kind) if kind.trim() == "pda" => {}
+        kind => {
+            return Err(JflapError::new(format!(
+                "expected a pda, found {}",
+                kind.unwrap_or_default().trim()
+            )))
+        }
+    }
+    let automaton = root.child("automaton").unwrap_or(&root);
+
+    let mut names = Vec::new();
+    let mut initial = None;
+    let mut accepting = Vec::new();
+    for state in automaton.children("state") {
+        let id = state
+            .attribute("id")
+            .ok_or(JflapError::new("state without an id"))?;
+        let name = state.attribute("name").unwrap_or(id).to_string();
+        if state.child("initial").is_some() {
+            initial = Some(name.clone());
+        }
+        if state.child("final").is_some() {
+            accepting.push(name.clone());
+        }
+        names.push((id.to_string(), name));
+    }
+    let initial = initial.ok_or(JflapError::new("no initial state"))?;
+    let name = |transition: &xml::Element, field: &str| {
+        let id = transition.child(field).map(xml::Element::text);
+        let id = id.as_deref().map(str::trim).unwrap_or_default();
+        names
+            .iter()
+            .find(|(i, _)| i == id)
+            .map(|(_, name)| name.clone())
+            .ok_or(JflapError::new(format!("unknown state id `{id}`")))
+    };
+
+    let mut transitions = Vec::new();
+    let mut stack_alphabet = BTreeSet::from([BOTTOM.to_string()]);
+    for transition in automaton.children("transition") {
+        let field = |field: &str| transition.child(field).map(xml::Element::text);
+        let read = field("read")
+            .unwrap_or_default()
+            .chars()
+            .collect::<Vec<_>>();
+        let pop = field("pop")
+            .unwrap_or_default()
+            .chars()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let push = field("push")
+            .unwrap_or_default()
+            .chars()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        stack_alphabet.extend(push.iter().cloned());
+        stack_alphabet.extend(pop.iter().cloned());
+        transitions.push((
+            name(transition, "from")?,
+            read,
+            pop,
+            name(transition, "to")?,
+            push,
+        ));
+    }
+
+    let mut states = names
+        .iter()
+        .map(|(_, name)| name.clone())
+        .collect::<HashSet<_>>();
+    let mut movements = Movements::new();
+    for (i, (from, read, pop, to, push)) in transitions.into_iter().enumerate() {
+        let steps = read.len().max(pop.len()).max(1);
+        let mut here = from.clone();
+        for step in 0..steps {
+            let (there, push) = if step + 1 == steps {
+                (to.clone(), push.clone())
+            } else {
+                let mut there = format!("{from}.{i}.{step}");
+                while !states.insert(there.clone()) {
+                    there.push('\'');
+                }
+                (there, Vec::new())
+            };
+            let v = read.get(step).copied();
+            // The last `pop.len()` steps pop, the ones before leave the stack as it is
+            match (step + pop.len()).checked_sub(steps) {
+                Some(j) => {
+                    movements.insert((here, v, pop[j].clone()), (there.clone(), push));
+                }
+                None => {
+                    for symbol in &stack_alphabet {
+                        let mut push = push.clone();
+                        push.push(symbol.clone());
+                        movements.insert((here.clone(), v, symbol.clone()), (there.clone(), push));
+                    }
+                }
+            }
+            here = there;
+        }
+    }
+    Ok(
+        AutomataBuilder::new(initial, vec![BOTTOM.to_string()], movements)
+            .accepting(Acceptance::FinalState, accepting),
+    )
+}
+
+/// Writes `automata_builder` as a JFLAP pushdown automaton, marking its accepting states as
+/// final. The acceptance mode itself is chosen in JFLAP when running the automaton.
+///
+/// Every stack symbol must be a single character. If the initial stack is not just `Z`,
+/// a new initial state replaces `Z` by the initial stack.
+pub fn to_jff(automata_builder: &TextAutomata) -> Result<String, JflapError> {
+    let movements = automata_builder.movements();
+    let mut symbols = movements.stack_symbols();
+    symbols.extend(automata_builder.initial_stack().iter().cloned());
+    if let Some(symbol) = symbols.iter().find(|s| s.chars().count() != 1) {
+        return Err(JflapError::new(format!(
+            "stack symbol `{symbol}` is not a single character"
+        )));
+    }
+
+    let mut states = movements.states().into_iter().collect::<BTreeSet<_>>();
+    states.insert(automata_builder.initial_state().clone());
+    states.extend(automata_builder.accepting_states().iter().cloned());
+    let mut transitions = movements
+        .iter()
+        .map(|((from, v, top), (to, push))| {
+            (from.clone(), *v, top.clone(), to.clone(), push.concat())
+        })
+        .collect::<Vec<_>>();
+    let mut initial = automata_builder.initial_state().clone();
+    let stack = automata_builder
+        .initial_stack()
+        .iter()
+        .cloned()
+        .collect::<String>();
+    if stack != BOTTOM {
+        let mut start = "start".to_string();
+        while states.contains(&start) {
+            start.push('\'');
+        }
+        transitions.push((start.clone(), None, BOTTOM.to_string(), initial, stack));
+        states.insert(start.clone());
+        initial = start;
+    }
+    transitions.sort();
+    let ids = states.iter().collect::<Vec<_>>();
+    let id = |state: &String| ids.iter().position(|s| *s == state).unwrap_or_default();
+
+    let mut jff = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+         <structure>\n\
+         \t<type>pda</type>\n\
+         \t<automaton>\n",
+    );
+    for (i, state) in ids.iter().enumerate() {
+        jff.push_str(&format!(
+            "\t\t<state id=\"{i}\" name=\"{}\">\n\t\t\t<x>{}.0</x>\n\t\t\t<y>100.0</y>\n",
+            xml::escape(state),
+            100 + 150 * i
+        ));
+        if **state == initial {
+            jff.push_str("\t\t\t<initial/>\n");
+        }
+        if automata_builder.accepting_states().contains(*state) {
+            jff.push_str("\t\t\t<final/>\n");
+        }
+        jff.push_str("\t\t</state>\n");
+    }
+    for (from, v, top, to, push) in &transitions {
+        let read = v.map(String::from).unwrap_or_default();
+        jff.push_str(&format!(
+            "\t\t<transition>\n\
+             \t\t\t<from>{}</from>\n\
+             \t\t\t<to>{}</to>\n\
+             \t\t\t{}\n\
+             \t\t\t<pop>{}</pop>\n\
+             \t\t\t{}\n\
+             \t\t</transition>\n",
+            id(from),
+            id(to),
+            xml::element("read", &read),
+            xml::escape(top),
+            xml::element("push", push),
+        ));
+    }
+    jff.push_str("\t</automaton>\n</structure>\n");
+    Ok(jff)
+}
+
+/// Just enough XML for `.jff` files: elements, attributes, text, comments and the
+/// predefined entities
+mod xml {
+    use super::JflapError;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Node {
+        Element(Element),
+        Text(String),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Element {
+        pub name: String,
+        pub attributes: Vec<(String, String)>,
+        pub children: Vec<Node>,
+    }
+
+    impl Element {
+        pub fn attribute(&self, name: &str) -> Option<&str> {
+            self.attributes
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value.as_str())
+        }
+
+        pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+            self.children.iter().filter_map(move |node| match node {
+                Node::Element(e) if e.name == name => Some(e),
+                _ => None,
+            })
+        }
+
+        pub fn child<'a>(&'a self, name: &'a str) -> Option<&'a Element> {
+            self.children(name).next()
+        }
+
+        pub fn text(&self) -> String {
+            self.children
+                .iter()
+                .filter_map(|node| match node {
+                    Node::Text(text) => Some(text.as_str()),
+                    Node::Element(_) => None,
+                })
+                .collect()
+        }
+    }
+
+    pub fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    /// An element holding `text`, self-closing when the text is empty
+    pub fn element(name: &str, text: &str) -> String {
+        if text.is_empty() {
+            format!("<{name}/>")
+        } else {
+            format!("<{name}>{}</{name}>", escape(text))
+        }
+    }
+
+    fn unescape(text: &str) -> String {
+        text.replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    }
+
+    /// Parses a document and returns its root element
+    pub fn parse(text: &str) -> Result<Element, JflapError> {
+        let mut stack = vec![Element {
+            name: String::new(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }];
+        let mut rest = text;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("<!--") {
+                let end = after
+                    .find("-->")
+                    .ok_or(JflapError::new("unclosed comment"))?;
+                rest = &after[end + 3..];
+            } else if let Some(after) = rest.strip_prefix("<?") {
+                let end = after
+                    .find("?>")
+                    .ok_or(JflapError::new("unclosed declaration"))?;
+                rest = &after[end + 2..];
+            } else if let Some(after) = rest.strip_prefix("</") {
+                let end = after.find('>').ok_or(JflapError::new("unclosed tag"))?;
+                let name = after[..end].trim();
+                let element = stack.pop().filter(|e| e.name == name && !stack.is_empty());
+                let element = element.ok_or(JflapError::new(format!("unexpected </{name}>")))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(Node::Element(element));
+                }
+                rest = &after[end + 1..];
+            } else if let Some(after) = rest.strip_prefix('<') {
+                let end = after.find('>').ok_or(JflapError::new("unclosed tag"))?;
+                let (tag, self_closing) = match after[..end].strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (&after[..end], false),
+                };
+                let element = start_tag(tag)?;
+                if self_closing {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(Node::Element(element));
+                    }
+                } else {
+                    stack.push(element);
+                }
+                rest = &after[end + 1..];
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(Node::Text(unescape(&rest[..end])));
+                }
+                rest = &rest[end..];
+            }
+        }
+        match &mut stack[..] {
+            [document] => document
+                .children
+                .drain(..)
+                .find_map(|node| match node {
+                    Node::Element(e) => Some(e),
+                    Node::Text(_) => None,
+                })
+                .ok_or(JflapError::new("empty document")),
+            [.., open] => Err(JflapError::new(format!("unclosed <{}>", open.name))),
+            [] => Err(JflapError::new("empty document")),
+        }
+    }
+
+    fn start_tag(tag: &str) -> Result<Element, JflapError> {
+        let tag = tag.trim();
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut element = Element {
+            name: tag[..name_end].to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+        let mut rest = tag[name_end..].trim_start();
+        while !rest.is_empty() {
+            let invalid = || JflapError::new(format!("invalid attributes in <{tag}>"));
+            let (name, after) = rest.split_once('=').ok_or_else(invalid)?;
+            let after = after.trim_start();
+            let quote = after.chars().next().filter(|c| *c == '"' || *c == '\'');
+            let quote = quote.ok_or_else(invalid)?;
+            let after = &after[1..];
+            let end = after.find(quote).ok_or_else(invalid)?;
+            element
+                .attributes
+                .push((name.trim().to_string(), unescape(&after[..end])));
+            rest = after[end + 1..].trim_start();
+        }
+        Ok(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Acceptance;
+
+    use super::{from_jff, to_jff};
+
+    const AN_BN: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?><!--Created with JFLAP 7.1.--><structure>
+	<type>pda</type>
+	<automaton>
+		<!--The list of states.-->
+		<state id="0" name="q0">
+			<x>61.0</x>
+			<y>117.0</y>
+			<initial/>
+		</state>
+		<state id="1" name="q1">
+			<x>229.0</x>
+			<y>117.0</y>
+		</state>
+		<state id="2" name="q2">
+			<x>389.0</x>
+			<y>117.0</y>
+			<final/>
+		</state>
+		<!--The list of transitions.-->
+		<transition>
+			<from>0</from>
+			<to>0</to>
+			<read>a</read>
+			<pop/>
+			<push>A</push>
+		</transition>
+		<transition>
+			<from>0</from>
+			<to>1</to>
+			<read>b</read>
+			<pop>A</pop>
+			<push/>
+		</transition>
+		<transition>
+			<from>1</from>
+			<to>1</to>
+			<read>b</read>
+			<pop>A</pop>
+			<push/>
+		</transition>
+		<transition>
+			<from>1</from>
+			<to>2</to>
+			<read/>
+			<pop>Z</pop>
+			<push>Z</push>
+		</transition>
+	</automaton>
+</structure>"#;
+
+    #[test]
+    fn test_from_jff() {
+        let automata_builder = from_jff(AN_BN).unwrap();
+        assert_eq!(automata_builder.initial_state(), "q0");
+        assert_eq!(automata_builder.acceptance(), Acceptance::FinalState);
+        assert!(automata_builder.accepting_states().contains("q2"));
+        // `a` with nothing popped becomes one transition for each of `A` and `Z`
+        assert_eq!(automata_builder.movements().len(), 5);
+        assert!(automata_builder.build("aabb".chars()).complete());
+        assert!(!automata_builder.build("aab".chars()).complete());
+        assert!(!automata_builder.build("".chars()).complete());
+    }
+
+    #[test]
+    fn test_jff_round_trip() {
+        let automata_builder = from_jff(AN_BN).unwrap();
+        let reread = from_jff(&to_jff(&automata_builder).unwrap()).unwrap();
+        assert_eq!(reread.initial_state(), automata_builder.initial_state());
+        assert_eq!(reread.movements(), automata_builder.movements());
+        assert_eq!(
+            reread.accepting_states(),
+            automata_builder.accepting_states()
+        );
+    }
+
+    #[test]
+    fn test_to_jff_initial_stack() {
+        let automata_builder = crate::format::parse(
+            "initial: p
+            stack: A B
+            p, a, A -> p, ε
+            p, b, B -> p, ε",
+        )
+        .unwrap();
+        let jff = to_jff(&automata_builder).unwrap();
+        assert!(jff.contains("<state id=\"1\" name=\"start\">"));
+        assert!(jff.contains("<pop>Z</pop>\n\t\t\t<push>AB</push>"));
+        let reread = from_jff(&jff)
+            .unwrap()
+            .accepting(Acceptance::EmptyStack, []);
+        assert!(reread.build("ab".chars()).complete());
+        assert!(!reread.build("ba".chars()).complete());
+
+        let automata_builder = crate::format::parse("initial: p\np, a, AB -> p, ε").unwrap();
+        assert!(to_jff(&automata_builder).is_err());
+    }
+
+    /// A `.jff` automaton with states `q0` (initial), `q1` and `q2` (final) and the given
+    /// `(from, to, read, pop, push)` transitions
+    fn jff(transitions: &[(usize, usize, &str, &str, &str)]) -> String {
+        let mut jff = String::from(
+            "<structure><type>pda</type><automaton>\
+             <state id=\"0\" name=\"q0\"><initial/></state>\
+             <state id=\"1\" name=\"q1\"/>\
+             <state id=\"2\" name=\"q2\"><final/></state>",
+        );
+        for (from, to, read, pop, push) in transitions {
+            jff.push_str(&format!(
+                "<transition><from>{from}</from><to>{to}</to><read>{read}</read>\
+                 <pop>{pop}</pop><push>{push}</push></transition>"
+            ));
+        }
+        jff.push_str("</automaton></structure>");
+        jff
+    }
+
+    #[test]
+    /// Test for (a^2n)(b^2n), popping two symbols for every `bb`
+    fn test_from_jff_multiple_symbols() {
+        let automata_builder = from_jff(&jff(&[
+            (0, 0, "aa", "", "AA"),
+            (0, 1, "bb", "AA", ""),
+            (1, 1, "bb", "AA", ""),
+            (1, 2, "", "Z", "Z"),
+        ]))
+        .unwrap();
+        assert!(automata_builder.build("aabb".chars()).complete());
+        assert!(automata_builder.build("aaaabbbb".chars()).complete());
+        assert!(!automata_builder.build("aaabbb".chars()).complete());
+        assert!(!automata_builder.build("aab".chars()).complete());
+
+        let reread = from_jff(&to_jff(&automata_builder).unwrap()).unwrap();
+        assert_eq!(reread.movements(), automata_builder.movements());
+    }
+
+    #[test]
+    /// JFLAP accepts `a` here by popping nothing on an empty stack, which the import can't
+    fn test_from_jff_empty_stack() {
+        let automata_builder = from_jff(&jff(&[(0, 1, "a", "Z", ""), (1, 2, "", "", "")])).unwrap();
+        assert!(!automata_builder.build("a".chars()).complete());
+
+        let automata_builder = from_jff(&jff(&[(0, 1, "a", "", ""), (1, 2, "", "", "")])).unwrap();
+        assert!(automata_builder.build("a".chars()).complete());
+    }
+
+    #[test]
+    fn test_from_jff_errors() {
+        assert!(from_jff("<structure><type>fa</type></structure>").is_err());
+        assert!(from_jff("<structure><type>pda</type><automaton>").is_err());
+        assert!(
+            from_jff("<structure><type>pda</type><automaton></automaton></structure>").is_err()
+        );
+    }
+}
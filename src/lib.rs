@@ -1,6 +1,36 @@
-use std::{collections::HashMap, hash::Hash};
+pub mod analysis;
+pub mod convert;
+pub mod cyk;
+pub mod dot;
+pub mod earley;
+pub mod equivalence;
+pub mod format;
+pub mod grammar;
+pub mod jflap;
+pub mod ll;
+pub mod lr;
+pub mod normal_form;
+pub mod rejection;
+pub mod trace;
+pub mod words;
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+#[cfg(feature = "serde")]
+mod serialize;
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    iter::Fuse,
+};
+
+use rejection::{Outcome, Rejection, RejectionReason};
+use trace::{Step, Trace};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Stack<StackData>(Vec<StackData>);
 
 impl<StackData> Stack<StackData> {
@@ -15,6 +45,23 @@ impl<StackData> Stack<StackData> {
     pub fn pop(&mut self) -> Option<StackData> {
         self.0.pop()
     }
+
+    pub fn peek(&self) -> Option<&StackData> {
+        self.0.last()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates from the top of the stack to the bottom
+    pub fn iter(&self) -> impl Iterator<Item = &StackData> {
+        self.0.iter().rev()
+    }
 }
 
 impl<F, T> From<F> for Stack<T>
@@ -26,62 +73,304 @@ where
     }
 }
 
-pub trait Movement<'a, 'b, VocabElement, StackData, Q>
-where
-    'a: 'b,
-{
+pub type MovementKey<VocabElement, StackData, Q> = (Q, Option<VocabElement>, StackData);
+pub type MovementTarget<StackData, Q> = (Q, Vec<StackData>);
+pub type Transition<VocabElement, StackData, Q> = (
+    MovementKey<VocabElement, StackData, Q>,
+    MovementTarget<StackData, Q>,
+);
+
+pub trait Movement<VocabElement, StackData, Q> {
     fn f(
-        &'a self,
+        &self,
         state: &Q,
         v: &Option<VocabElement>,
         s: &StackData,
-    ) -> Option<&'b (Q, Vec<StackData>)>;
+    ) -> Vec<MovementTarget<StackData, Q>>;
+}
+
+#[derive(Debug, Clone)]
+pub struct Movements<VocabElement, StackData, Q>(
+    HashMap<MovementKey<VocabElement, StackData, Q>, HashSet<MovementTarget<StackData, Q>>>,
+);
+
+impl<VocabElement, StackData, Q> Movements<VocabElement, StackData, Q> {
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Number of transitions, counting every successor of a key separately
+    pub fn len(&self) -> usize {
+        self.0.values().map(HashSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &MovementKey<VocabElement, StackData, Q>,
+            &MovementTarget<StackData, Q>,
+        ),
+    > {
+        self.0
+            .iter()
+            .flat_map(|(from, to)| to.iter().map(move |to| (from, to)))
+    }
+}
+
+impl<VocabElement, StackData, Q> Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Hash + Eq,
+    StackData: Hash + Eq,
+    Q: Hash + Eq,
+{
+    /// Adds a successor for `from`, keeping any successors already present.
+    /// Returns whether the transition was new.
+    pub fn insert(
+        &mut self,
+        from: MovementKey<VocabElement, StackData, Q>,
+        to: MovementTarget<StackData, Q>,
+    ) -> bool {
+        self.0.entry(from).or_default().insert(to)
+    }
+
+    pub fn get(
+        &self,
+        from: &MovementKey<VocabElement, StackData, Q>,
+    ) -> Option<&HashSet<MovementTarget<StackData, Q>>> {
+        self.0.get(from)
+    }
+
+    /// Removes every successor of `from`
+    pub fn remove(
+        &mut self,
+        from: &MovementKey<VocabElement, StackData, Q>,
+    ) -> Option<HashSet<MovementTarget<StackData, Q>>> {
+        self.0.remove(from)
+    }
+
+    /// Every state mentioned by a transition, either as source or as target
+    pub fn states(&self) -> HashSet<Q>
+    where
+        Q: Clone,
+    {
+        self.iter()
+            .flat_map(|((from, _, _), (to, _))| [from.clone(), to.clone()])
+            .collect()
+    }
+
+    /// Every stack symbol mentioned by a transition, either as top or as pushed
+    pub fn stack_symbols(&self) -> HashSet<StackData>
+    where
+        StackData: Clone,
+    {
+        self.iter()
+            .flat_map(|((_, _, top), (_, push))| std::iter::once(top).chain(push))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<VocabElement, StackData, Q> PartialEq for Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Hash + Eq,
+    StackData: Hash + Eq,
+    Q: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<VocabElement, StackData, Q> Eq for Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Hash + Eq,
+    StackData: Hash + Eq,
+    Q: Hash + Eq,
+{
+}
+
+impl<VocabElement, StackData, Q> Default for Movements<VocabElement, StackData, Q> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<VocabElement, StackData, Q>
+    FromIterator<(
+        MovementKey<VocabElement, StackData, Q>,
+        MovementTarget<StackData, Q>,
+    )> for Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Hash + Eq,
+    StackData: Hash + Eq,
+    Q: Hash + Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<
+            Item = (
+                MovementKey<VocabElement, StackData, Q>,
+                MovementTarget<StackData, Q>,
+            ),
+        >,
+    {
+        let mut movements = Self::new();
+        movements.extend(iter);
+        movements
+    }
 }
 
-pub type Movements<VocabElement, StackData, Q> =
-    HashMap<(Q, Option<VocabElement>, StackData), (Q, Vec<StackData>)>;
+impl<VocabElement, StackData, Q>
+    Extend<(
+        MovementKey<VocabElement, StackData, Q>,
+        MovementTarget<StackData, Q>,
+    )> for Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Hash + Eq,
+    StackData: Hash + Eq,
+    Q: Hash + Eq,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<
+            Item = (
+                MovementKey<VocabElement, StackData, Q>,
+                MovementTarget<StackData, Q>,
+            ),
+        >,
+    {
+        for (from, to) in iter {
+            self.insert(from, to);
+        }
+    }
+}
 
-impl<'a, 'b, VocabElement, StackData, Q> Movement<'a, 'b, VocabElement, StackData, Q>
+impl<VocabElement, StackData, Q> Movement<VocabElement, StackData, Q>
     for Movements<VocabElement, StackData, Q>
 where
-    (Q, Option<VocabElement>, StackData): Hash + Eq,
-    StackData: Clone,
-    Q: Clone,
-    VocabElement: Clone,
-    'a: 'b,
+    VocabElement: Hash + Eq + Clone,
+    StackData: Hash + Eq + Clone,
+    Q: Hash + Eq + Clone,
 {
     fn f(
-        &'a self,
+        &self,
         state: &Q,
         v: &Option<VocabElement>,
         s: &StackData,
-    ) -> Option<&'b (Q, Vec<StackData>)> {
+    ) -> Vec<MovementTarget<StackData, Q>> {
         self.get(&(state.clone(), v.clone(), s.clone()))
+            .map(|to| to.iter().cloned().collect())
+            .unwrap_or_default()
     }
 }
 
-impl<'a, 'b, VocabElement, StackData, Q, F> Movement<'a, 'b, VocabElement, StackData, Q> for F
+impl<VocabElement, StackData, Q, F> Movement<VocabElement, StackData, Q> for F
 where
-    F: Fn(&Q, &Option<VocabElement>, &StackData) -> Option<&'b (Q, Vec<StackData>)> + 'a,
-    StackData: 'b,
-    Q: 'b,
-    'a: 'b,
+    F: Fn(&Q, &Option<VocabElement>, &StackData) -> Vec<MovementTarget<StackData, Q>>,
 {
     fn f(
-        &'a self,
+        &self,
         state: &Q,
         v: &Option<VocabElement>,
         s: &StackData,
-    ) -> Option<&'b (Q, Vec<StackData>)> {
+    ) -> Vec<MovementTarget<StackData, Q>> {
         self(state, v, s)
     }
 }
 
+/// How an automaton decides that a word has been accepted once the input is exhausted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Acceptance {
+    /// The stack is empty
+    #[default]
+    EmptyStack,
+    /// The automaton is in one of its accepting states
+    FinalState,
+    /// The stack is empty and the automaton is in one of its accepting states
+    Both,
+}
+
+/// Limits on how far a run is simulated.
+///
+/// By default the number of steps is unbounded and the stack depth is bounded by
+/// [`Budget::DEFAULT_STACK_DEPTH`]. Branches are never explored twice from the same
+/// configuration, and with a bounded stack there are finitely many of them, so every run
+/// ends. Without a bound on the stack depth, epsilon movements that keep growing the stack
+/// make [`Automata::finish`] loop forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Budget {
+    steps: Option<usize>,
+    stack_depth: Option<usize>,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::unlimited().max_stack_depth(Self::DEFAULT_STACK_DEPTH)
+    }
+}
+
+impl Budget {
+    pub const DEFAULT_STACK_DEPTH: usize = 1024;
+
+    /// No limit at all, so runs may not terminate, see [`Automata::finish`]
+    pub fn unlimited() -> Self {
+        Self {
+            steps: None,
+            stack_depth: None,
+        }
+    }
+
+    /// Maximum number of calls to [`Automata::run`]
+    pub fn max_steps(mut self, steps: usize) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
+    /// Maximum number of symbols on the stack of any branch
+    pub fn max_stack_depth(mut self, depth: usize) -> Self {
+        self.stack_depth = Some(depth);
+        self
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "StackData: serde::Serialize, Q: serde::Serialize + Ord, M: serde::Serialize",
+        deserialize = "StackData: serde::Deserialize<'de>, \
+                       Q: serde::Deserialize<'de> + Hash + Eq, \
+                       M: serde::Deserialize<'de>"
+    ))
+)]
 pub struct AutomataBuilder<StackData, Q, M> {
+    #[cfg_attr(feature = "serde", serde(rename = "initial_state"))]
     state: Q,
+    #[cfg_attr(feature = "serde", serde(rename = "initial_stack"))]
     stack: Stack<StackData>,
     movements: M,
+    #[cfg_attr(feature = "serde", serde(default))]
+    acceptance: Acceptance,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            rename = "accepting_states",
+            serialize_with = "serialize::sorted"
+        )
+    )]
+    accepting: HashSet<Q>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    budget: Budget,
 }
 
 impl<StackData, Q, M> AutomataBuilder<StackData, Q, M> {
@@ -93,17 +382,54 @@ impl<StackData, Q, M> AutomataBuilder<StackData, Q, M> {
             state: initial_state,
             stack: initial_stack.into(),
             movements,
+            acceptance: Acceptance::default(),
+            accepting: HashSet::new(),
+            budget: Budget::default(),
         }
     }
 
-    pub fn build<'a, 'b, V, W>(&self, word: W) -> Automata<V, StackData, Q, W, M>
+    pub fn accepting<I>(mut self, acceptance: Acceptance, accepting_states: I) -> Self
+    where
+        I: IntoIterator<Item = Q>,
+        Q: Hash + Eq,
+    {
+        self.acceptance = acceptance;
+        self.accepting = accepting_states.into_iter().collect();
+        self
+    }
+
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn initial_state(&self) -> &Q {
+        &self.state
+    }
+
+    pub fn initial_stack(&self) -> &Stack<StackData> {
+        &self.stack
+    }
+
+    pub fn movements(&self) -> &M {
+        &self.movements
+    }
+
+    pub fn acceptance(&self) -> Acceptance {
+        self.acceptance
+    }
+
+    pub fn accepting_states(&self) -> &HashSet<Q> {
+        &self.accepting
+    }
+
+    pub fn build<V, W>(&self, word: W) -> Automata<V, StackData, Q, W, M>
     where
         W: Iterator<Item = V>,
-        Q: Clone,
-        StackData: Clone,
+        Q: Clone + Hash + Eq,
+        StackData: Clone + Hash + Eq,
         M: Clone,
-        M: Movement<'a, 'b, V, StackData, Q>,
-        'a: 'b,
+        M: Movement<V, StackData, Q>,
     {
         Automata::new(
             word,
@@ -111,25 +437,94 @@ impl<StackData, Q, M> AutomataBuilder<StackData, Q, M> {
             self.stack.clone(),
             self.movements.clone(),
         )
+        .accepting(self.acceptance, self.accepting.iter().cloned())
+        .budget(self.budget)
+    }
+}
+
+/// A snapshot of one branch of a run: its state, its stack and how much of the word it has
+/// consumed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Configuration<StackData, Q> {
+    state: Q,
+    stack: Stack<StackData>,
+    consumed: usize,
+}
+
+impl<StackData, Q> Configuration<StackData, Q> {
+    pub fn new<S>(state: Q, stack: S, consumed: usize) -> Self
+    where
+        S: Into<Stack<StackData>>,
+    {
+        Self {
+            state,
+            stack: stack.into(),
+            consumed,
+        }
+    }
+
+    pub fn state(&self) -> &Q {
+        &self.state
+    }
+
+    pub fn stack(&self) -> &Stack<StackData> {
+        &self.stack
+    }
+
+    /// Number of input symbols consumed so far
+    pub fn consumed(&self) -> usize {
+        self.consumed
     }
 }
 
+#[derive(Debug, Clone)]
+struct Node<VocabElement, StackData, Q> {
+    configuration: Configuration<StackData, Q>,
+    parent: Option<usize>,
+    movement: Option<Transition<VocabElement, StackData, Q>>,
+    /// Whether some movement applied, even if it led to a configuration already reached
+    moved: bool,
+}
+
+/// Simulates a (possibly nondeterministic) pushdown automaton.
+///
+/// Every branch is explored breadth first, one movement per call to [`Automata::run`].
+/// Configurations that were already reached by another branch are not explored again,
+/// so branches that share a prefix are only simulated once.
+///
+/// Epsilon movements (those with a `None` input) may fire at any point without
+/// consuming input. Wherever both an epsilon movement and a movement on the next
+/// symbol apply, the simulation branches and follows both.
 #[derive(Debug, Clone)]
 pub struct Automata<VocabElement, StackData, Q, Word, M>
 where
     Word: Iterator<Item = VocabElement>,
 {
-    state: Q,
-    stack: Stack<StackData>,
-    word: Word,
+    nodes: Vec<Node<VocabElement, StackData, Q>>,
+    configurations: Vec<usize>,
+    seen: HashSet<Configuration<StackData, Q>>,
+    word: Fuse<Word>,
+    input: Vec<VocabElement>,
     movements: M,
+    acceptance: Acceptance,
+    accepting: HashSet<Q>,
+    budget: Budget,
+    steps: usize,
+    cycled: bool,
+    truncated: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AutomataResult {
     Accept,
     Processing,
     NotAccepting,
+    /// No branch accepted, and at least one branch went back to a configuration it had
+    /// already been in, so it would have looped forever
+    Diverged,
+    /// No branch accepted before the [`Budget`] ran out
+    BudgetExceeded,
 }
 
 impl<VocabElement, StackData, Q, Word, M> Automata<VocabElement, StackData, Q, Word, M>
@@ -139,66 +534,348 @@ where
     pub fn new<S>(word: Word, initial_state: Q, initial_stack: S, movements: M) -> Self
     where
         S: Into<Stack<StackData>>,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
     {
-        Self {
+        let initial = Configuration {
             state: initial_state,
             stack: initial_stack.into(),
-            word,
+            consumed: 0,
+        };
+        Self {
+            seen: HashSet::from([initial.clone()]),
+            nodes: vec![Node {
+                configuration: initial,
+                parent: None,
+                movement: None,
+                moved: false,
+            }],
+            configurations: vec![0],
+            word: word.fuse(),
+            input: Vec::new(),
             movements,
+            acceptance: Acceptance::default(),
+            accepting: HashSet::new(),
+            budget: Budget::default(),
+            steps: 0,
+            cycled: false,
+            truncated: false,
         }
     }
 
-    pub fn run<'a, 'b>(&mut self) -> AutomataResult
+    pub fn accepting<I>(mut self, acceptance: Acceptance, accepting_states: I) -> Self
     where
-        (Q, Option<VocabElement>, StackData): Hash + Eq,
-        StackData: Clone,
-        Q: Clone,
-        M: Movement<'a, 'b, VocabElement, StackData, Q>,
-        'a: 'b,
+        I: IntoIterator<Item = Q>,
+        Q: Hash + Eq,
     {
-        let v = self.word.next();
-        let s = self.stack.pop();
-        match (v, s) {
-            (None, None) => AutomataResult::Accept,
-            (v, Some(s)) => {
-                let m = self.movements.f(&self.state, &v, &s).cloned();
-
-                if let Some((state, new_stack)) = m {
-                    self.state = state.clone();
-                    for elem in new_stack.iter().rev() {
-                        self.stack.push(elem.clone());
-                    }
-                    AutomataResult::Processing
+        self.acceptance = acceptance;
+        self.accepting = accepting_states.into_iter().collect();
+        self
+    }
+
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// The configurations of every live branch
+    pub fn configurations(&self) -> impl Iterator<Item = &Configuration<StackData, Q>> {
+        self.configurations
+            .iter()
+            .map(|&index| &self.nodes[index].configuration)
+    }
+
+    /// The current configuration, as long as there is exactly one live branch
+    pub fn configuration(&self) -> Option<&Configuration<StackData, Q>> {
+        match self.configurations[..] {
+            [index] => Some(&self.nodes[index].configuration),
+            _ => None,
+        }
+    }
+
+    /// The current state, as long as there is exactly one live branch
+    pub fn state(&self) -> Option<&Q> {
+        self.configuration().map(Configuration::state)
+    }
+
+    /// The current stack, as long as there is exactly one live branch
+    pub fn stack(&self) -> Option<&Stack<StackData>> {
+        self.configuration().map(Configuration::stack)
+    }
+
+    fn accepts(&self, configuration: &Configuration<StackData, Q>) -> bool
+    where
+        Q: Hash + Eq,
+    {
+        let empty = configuration.stack.is_empty();
+        let final_state = self.accepting.contains(&configuration.state);
+        match self.acceptance {
+            Acceptance::EmptyStack => empty,
+            Acceptance::FinalState => final_state,
+            Acceptance::Both => empty && final_state,
+        }
+    }
+
+    fn symbol(&mut self, position: usize) -> Option<&VocabElement> {
+        while self.input.len() <= position {
+            self.input.push(self.word.next()?);
+        }
+        self.input.get(position)
+    }
+
+    pub fn run(&mut self) -> AutomataResult
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+        M: Movement<VocabElement, StackData, Q>,
+    {
+        if self.budget.steps.is_some_and(|steps| self.steps >= steps) {
+            return AutomataResult::BudgetExceeded;
+        }
+        self.steps += 1;
+        let mut next = Vec::new();
+        for index in std::mem::take(&mut self.configurations) {
+            let configuration = self.nodes[index].configuration.clone();
+            let v = self.symbol(configuration.consumed).cloned();
+            if v.is_none() && self.accepts(&configuration) {
+                self.configurations = vec![index];
+                return AutomataResult::Accept;
+            }
+            let mut stack = configuration.stack;
+            let Some(s) = stack.pop() else {
+                continue;
+            };
+            let epsilon = self
+                .movements
+                .f(&configuration.state, &None, &s)
+                .into_iter()
+                .map(|m| (None, m, configuration.consumed));
+            let consuming = match v {
+                Some(_) => self.movements.f(&configuration.state, &v, &s),
+                None => Vec::new(),
+            }
+            .into_iter()
+            .map(|m| (v.clone(), m, configuration.consumed + 1));
+            for (v, (state, new_stack), consumed) in epsilon.chain(consuming) {
+                self.nodes[index].moved = true;
+                let mut stack = stack.clone();
+                for elem in new_stack.iter().rev() {
+                    stack.push(elem.clone());
+                }
+                if self
+                    .budget
+                    .stack_depth
+                    .is_some_and(|depth| stack.len() > depth)
+                {
+                    self.truncated = true;
+                    continue;
+                }
+                let successor = Configuration {
+                    state: state.clone(),
+                    stack,
+                    consumed,
+                };
+                if self.seen.contains(&successor) {
+                    self.cycled |= self.is_ancestor(&successor, index);
                 } else {
-                    AutomataResult::NotAccepting
+                    self.seen.insert(successor.clone());
+                    next.push(self.nodes.len());
+                    self.nodes.push(Node {
+                        configuration: successor,
+                        parent: Some(index),
+                        movement: Some((
+                            (configuration.state.clone(), v, s.clone()),
+                            (state, new_stack),
+                        )),
+                        moved: false,
+                    });
                 }
             }
-            _ => AutomataResult::NotAccepting,
+        }
+        self.configurations = next;
+        if !self.configurations.is_empty() {
+            AutomataResult::Processing
+        } else if self.truncated {
+            AutomataResult::BudgetExceeded
+        } else if self.cycled {
+            AutomataResult::Diverged
+        } else {
+            AutomataResult::NotAccepting
         }
     }
 
-    pub fn complete<'a, 'b>(mut self) -> bool
+    /// The last node among those that consumed the most input, preferring the ones where no
+    /// movement applied. A node whose movements only led to configurations reached by
+    /// other branches did not get stuck there, those branches went on.
+    fn furthest(&self) -> usize {
+        self.nodes
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, node)| (node.configuration.consumed, !node.moved))
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// Whether `configuration` is the one in node `index` or in one of its ancestors.
+    /// Only epsilon movements can lead back to a configuration, so the search stops at the
+    /// first ancestor that had consumed less input.
+    fn is_ancestor(&self, configuration: &Configuration<StackData, Q>, index: usize) -> bool
     where
-        (Q, Option<VocabElement>, StackData): Hash + Eq,
-        StackData: Clone,
-        Q: Clone,
-        M: Movement<'a, 'b, VocabElement, StackData, Q>,
-        'a: 'b,
-        Self: 'a,
+        StackData: Eq,
+        Q: Eq,
+    {
+        let mut index = Some(index);
+        while let Some(i) = index {
+            let node = &self.nodes[i];
+            if node.configuration.consumed != configuration.consumed {
+                return false;
+            }
+            if &node.configuration == configuration {
+                return true;
+            }
+            index = node.parent;
+        }
+        false
+    }
+
+    /// Calls [`Automata::run`] until the result is no longer [`AutomataResult::Processing`].
+    ///
+    /// This always terminates when the [`Budget`] bounds the steps or the stack depth, as
+    /// the default one does. With [`Budget::unlimited`] it loops forever on a word no branch
+    /// accepts if epsilon movements can grow the stack without bound.
+    pub fn finish(&mut self) -> AutomataResult
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+        M: Movement<VocabElement, StackData, Q>,
     {
         let mut r = AutomataResult::Processing;
         while r == AutomataResult::Processing {
             r = self.run();
         }
-        r == AutomataResult::Accept
+        r
+    }
+
+    /// Whether the word is accepted, running with [`Automata::finish`] and so only
+    /// guaranteed to terminate with a bounded [`Budget`]
+    pub fn complete(mut self) -> bool
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+        M: Movement<VocabElement, StackData, Q>,
+    {
+        self.finish() == AutomataResult::Accept
+    }
+
+    /// Runs the automaton to completion and explains why the word was rejected, looking at
+    /// the same branch as [`Automata::trace`]
+    pub fn outcome(mut self) -> Outcome<VocabElement, StackData, Q>
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+        M: Movement<VocabElement, StackData, Q>,
+    {
+        let r = self.finish();
+        self.rejection(r)
+    }
+
+    /// Runs the automaton to completion, recording the configurations along one branch.
+    ///
+    /// If the word is accepted the branch is the accepting one. Otherwise it is a branch
+    /// that consumed as much of the word as any other before getting stuck.
+    pub fn trace(mut self) -> Trace<VocabElement, StackData, Q>
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+        M: Movement<VocabElement, StackData, Q>,
+    {
+        let r = self.finish();
+        self.into_trace(r)
+    }
+
+    /// Both [`Automata::outcome`] and [`Automata::trace`] from a single run
+    pub fn outcome_and_trace(
+        mut self,
+    ) -> (
+        Outcome<VocabElement, StackData, Q>,
+        Trace<VocabElement, StackData, Q>,
+    )
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+        M: Movement<VocabElement, StackData, Q>,
+    {
+        let r = self.finish();
+        (self.rejection(r), self.into_trace(r))
+    }
+
+    /// Why a finished run with result `r` rejected its word, if it did
+    fn rejection(&mut self, r: AutomataResult) -> Outcome<VocabElement, StackData, Q>
+    where
+        VocabElement: Clone,
+        StackData: Clone + Hash + Eq,
+        Q: Clone + Hash + Eq,
+    {
+        let configuration = self.nodes[self.furthest()].configuration.clone();
+        let reason = match r {
+            AutomataResult::Accept => return Ok(()),
+            AutomataResult::Diverged => RejectionReason::Diverged,
+            AutomataResult::BudgetExceeded => RejectionReason::BudgetExceeded,
+            AutomataResult::Processing | AutomataResult::NotAccepting => {
+                let v = self.symbol(configuration.consumed).cloned();
+                match (v, configuration.stack.peek()) {
+                    (Some(symbol), Some(top)) => RejectionReason::NoMovement {
+                        symbol,
+                        top: top.clone(),
+                    },
+                    (Some(_), None) => RejectionReason::StackEmptied,
+                    (None, _)
+                        if self.acceptance != Acceptance::EmptyStack
+                            && !self.accepting.contains(&configuration.state) =>
+                    {
+                        RejectionReason::NotAccepting
+                    }
+                    (None, _) => RejectionReason::StackNotEmpty,
+                }
+            }
+        };
+        Err(Rejection::new(reason, configuration))
+    }
+
+    /// The branch of a finished run with result `r` that [`Automata::trace`] records
+    fn into_trace(mut self, r: AutomataResult) -> Trace<VocabElement, StackData, Q>
+    where
+        VocabElement: Clone,
+        StackData: Clone,
+        Q: Clone,
+    {
+        let mut index = match r {
+            AutomataResult::Accept => Some(self.configurations[0]),
+            _ => Some(self.furthest()),
+        };
+        let mut steps = Vec::new();
+        while let Some(i) = index {
+            let node = &self.nodes[i];
+            steps.push(Step::new(node.configuration.clone(), node.movement.clone()));
+            index = node.parent;
+        }
+        steps.reverse();
+        self.input.extend(&mut self.word);
+        Trace::new(self.input, steps, r)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
-    use crate::{AutomataBuilder, Movements};
+    use crate::{
+        fixtures::an_bn, rejection::RejectionReason, Acceptance, AutomataBuilder, AutomataResult,
+        Budget, Configuration, Movements, Stack,
+    };
 
     #[test]
     /// Test for (a^n)(b^n) where n >= 1
@@ -228,7 +905,7 @@ mod tests {
         }
         use Vocab::*;
 
-        let mut ruleset: Movements<Vocab, StackElement, State> = HashMap::new();
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
         ruleset.insert((Q0, Some(a), A0), (Q0, vec![A]));
         ruleset.insert((Q0, Some(a), A), (Q0, vec![A, A]));
         ruleset.insert((Q0, Some(b), A), (Q1, vec![]));
@@ -273,7 +950,7 @@ mod tests {
         }
         use Vocab::*;
 
-        let mut ruleset: Movements<Vocab, StackElement, State> = HashMap::new();
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
         ruleset.insert((Q0, Some(a), A0), (Q0, vec![A, A0]));
         ruleset.insert((Q0, Some(a), A), (Q0, vec![A, A]));
         ruleset.insert((Q0, Some(b), A), (Q1, vec![]));
@@ -290,4 +967,318 @@ mod tests {
         assert!(!automata_builder.build([b].into_iter()).complete());
         assert!(!automata_builder.build([].into_iter()).complete());
     }
+
+    #[test]
+    /// Test for w(w^R) where w in {a, b}*, guessing the middle on a repeated symbol
+    fn test_even_palindromes_nondeterministic() {
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum State {
+            Q0,
+            Q1,
+        }
+
+        use State::*;
+
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum StackElement {
+            Z,
+            A,
+            B,
+        }
+
+        use StackElement::*;
+
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum Vocab {
+            a,
+            b,
+        }
+        use Vocab::*;
+
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
+        for top in [Z, A, B] {
+            ruleset.insert((Q0, Some(a), top.clone()), (Q0, vec![A, top.clone()]));
+            ruleset.insert((Q0, Some(b), top.clone()), (Q0, vec![B, top]));
+        }
+        ruleset.insert((Q0, Some(a), A), (Q1, vec![]));
+        ruleset.insert((Q0, Some(b), B), (Q1, vec![]));
+        ruleset.insert((Q1, Some(a), A), (Q1, vec![]));
+        ruleset.insert((Q1, Some(b), B), (Q1, vec![]));
+        ruleset.insert((Q0, None, Z), (Q0, vec![]));
+        ruleset.insert((Q1, None, Z), (Q1, vec![]));
+        assert_eq!(ruleset.len(), 12);
+
+        let automata_builder = AutomataBuilder::new(Q0, vec![Z], ruleset);
+        assert!(automata_builder.build([].into_iter()).complete());
+        assert!(automata_builder.build([a, a].into_iter()).complete());
+        assert!(automata_builder.build([a, b, b, a].into_iter()).complete());
+        assert!(automata_builder
+            .build([b, a, a, b, b, a, a, b].into_iter())
+            .complete());
+        assert!(!automata_builder.build([a, b].into_iter()).complete());
+        assert!(!automata_builder.build([a, b, a].into_iter()).complete());
+        assert!(!automata_builder.build([a, a, b, b].into_iter()).complete());
+
+        let long = std::iter::repeat_n(a, 200);
+        assert!(automata_builder.build(long).complete());
+    }
+
+    #[test]
+    /// Test for w(w^R) and wx(w^R) where w in {a, b}* and x in {a, b},
+    /// guessing the middle with an epsilon movement
+    fn test_palindromes_epsilon_middle() {
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum State {
+            Q0,
+            Q1,
+        }
+
+        use State::*;
+
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum StackElement {
+            Z,
+            A,
+            B,
+        }
+
+        use StackElement::*;
+
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum Vocab {
+            a,
+            b,
+        }
+        use Vocab::*;
+
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
+        for top in [Z, A, B] {
+            ruleset.insert((Q0, Some(a), top.clone()), (Q0, vec![A, top.clone()]));
+            ruleset.insert((Q0, Some(b), top.clone()), (Q0, vec![B, top.clone()]));
+            ruleset.insert((Q0, Some(a), top.clone()), (Q1, vec![top.clone()]));
+            ruleset.insert((Q0, Some(b), top.clone()), (Q1, vec![top.clone()]));
+            ruleset.insert((Q0, None, top.clone()), (Q1, vec![top]));
+        }
+        ruleset.insert((Q1, Some(a), A), (Q1, vec![]));
+        ruleset.insert((Q1, Some(b), B), (Q1, vec![]));
+        ruleset.insert((Q1, None, Z), (Q1, vec![]));
+
+        let automata_builder = AutomataBuilder::new(Q0, vec![Z], ruleset);
+        assert!(automata_builder.build([].into_iter()).complete());
+        assert!(automata_builder.build([a].into_iter()).complete());
+        assert!(automata_builder.build([a, b, a].into_iter()).complete());
+        assert!(automata_builder.build([a, b, b, a].into_iter()).complete());
+        assert!(automata_builder
+            .build([b, a, b, a, b].into_iter())
+            .complete());
+        assert!(!automata_builder.build([a, b].into_iter()).complete());
+        assert!(!automata_builder.build([a, a, b].into_iter()).complete());
+        assert!(!automata_builder.build([a, b, b, b].into_iter()).complete());
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, accepting by final state and by both criteria
+    fn test_an_bn_n_ge_1_final_state() {
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum State {
+            Q0,
+            Q1,
+            Q2,
+        }
+
+        use State::*;
+
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum StackElement {
+            A0,
+            A,
+        }
+
+        use StackElement::*;
+
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+        enum Vocab {
+            a,
+            b,
+        }
+        use Vocab::*;
+
+        let mut ruleset: Movements<Vocab, StackElement, State> = Movements::new();
+        ruleset.insert((Q0, Some(a), A0), (Q0, vec![A, A0]));
+        ruleset.insert((Q0, Some(a), A), (Q0, vec![A, A]));
+        ruleset.insert((Q0, Some(b), A), (Q1, vec![]));
+        ruleset.insert((Q1, Some(b), A), (Q1, vec![]));
+        ruleset.insert((Q1, None, A0), (Q2, vec![A0]));
+
+        let automata_builder = AutomataBuilder::new(Q0, vec![A0], ruleset.clone())
+            .accepting(Acceptance::FinalState, [Q2]);
+        assert!(automata_builder.build([a, b].into_iter()).complete());
+        assert!(automata_builder
+            .build([a, a, a, b, b, b].into_iter())
+            .complete());
+        assert!(!automata_builder.build([a, a, b].into_iter()).complete());
+        assert!(!automata_builder.build([a, b, b].into_iter()).complete());
+        assert!(!automata_builder.build([].into_iter()).complete());
+
+        // Q2 is reached with A0 still on the stack, so it must be popped as well
+        let automata_builder =
+            AutomataBuilder::new(Q0, vec![A0], ruleset.clone()).accepting(Acceptance::Both, [Q2]);
+        assert!(!automata_builder.build([a, b].into_iter()).complete());
+
+        ruleset.insert((Q2, None, A0), (Q2, vec![]));
+        let automata_builder =
+            AutomataBuilder::new(Q0, vec![A0], ruleset).accepting(Acceptance::Both, [Q2]);
+        assert!(automata_builder.build([a, b].into_iter()).complete());
+        assert!(!automata_builder.build([a, a, b].into_iter()).complete());
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, inspecting the configuration between steps
+    fn test_an_bn_n_ge_1_configurations() {
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], an_bn());
+        let mut automata = automata_builder.build("aab".chars());
+        assert_eq!(automata.state(), Some(&"q0"));
+        assert_eq!(automata.stack().and_then(Stack::peek), Some(&'Z'));
+
+        assert_eq!(automata.run(), AutomataResult::Processing);
+        assert_eq!(automata.run(), AutomataResult::Processing);
+        let stack = automata.stack().unwrap();
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack.iter().collect::<String>(), "AAZ");
+        let snapshot = automata.configuration().unwrap().clone();
+        assert_eq!(snapshot, Configuration::new("q0", vec!['Z', 'A', 'A'], 2));
+
+        assert_eq!(automata.run(), AutomataResult::Processing);
+        assert_eq!(automata.state(), Some(&"q1"));
+        assert_eq!(
+            automata.configuration().map(Configuration::consumed),
+            Some(3)
+        );
+        assert_ne!(automata.configuration(), Some(&snapshot));
+        assert_eq!(automata.run(), AutomataResult::NotAccepting);
+        assert_eq!(automata.configurations().count(), 0);
+        assert_eq!(automata.state(), None);
+    }
+
+    #[test]
+    fn test_budget_and_divergence() {
+        let mut ruleset = Movements::new();
+        ruleset.insert(('p', Some('a'), 'Z'), ('p', vec!['Z']));
+        // Grows the stack forever without consuming input
+        ruleset.insert(('p', None, 'Z'), ('q', vec!['A', 'Z']));
+        ruleset.insert(('q', None, 'A'), ('q', vec!['A', 'A']));
+        // Loops forever on the same configuration
+        ruleset.insert(('p', Some('b'), 'Z'), ('r', vec!['Z']));
+        ruleset.insert(('r', None, 'Z'), ('r', vec!['Z']));
+
+        let automata_builder = AutomataBuilder::new('p', vec!['Z'], ruleset.clone())
+            .budget(Budget::unlimited().max_stack_depth(16));
+        let mut automata = automata_builder.build("aa".chars());
+        assert_eq!(automata.finish(), AutomataResult::BudgetExceeded);
+        assert_eq!(automata.run(), AutomataResult::BudgetExceeded);
+        assert!(!automata_builder.build("aa".chars()).complete());
+
+        let automata_builder = AutomataBuilder::new('p', vec!['Z'], ruleset.clone())
+            .budget(Budget::unlimited().max_steps(10));
+        let mut automata = automata_builder.build("aa".chars());
+        assert_eq!(automata.finish(), AutomataResult::BudgetExceeded);
+        assert!(automata.configurations().count() > 0);
+
+        let automata_builder = AutomataBuilder::new('p', vec!['Z'], ruleset.clone());
+        let mut automata = automata_builder.build("aa".chars());
+        assert_eq!(automata.finish(), AutomataResult::BudgetExceeded);
+        assert!(!automata_builder.build("aa".chars()).complete());
+
+        ruleset.remove(&('p', None, 'Z'));
+        let automata_builder = AutomataBuilder::new('p', vec!['Z'], ruleset);
+        let mut automata = automata_builder.build("ab".chars());
+        assert_eq!(automata.finish(), AutomataResult::Diverged);
+        assert_eq!(automata.run(), AutomataResult::Diverged);
+        assert_eq!(
+            automata_builder.build("aa".chars()).finish(),
+            AutomataResult::NotAccepting
+        );
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1, explaining rejections
+    fn test_an_bn_n_ge_1_rejections() {
+        let mut ruleset = an_bn();
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], ruleset.clone());
+
+        assert_eq!(automata_builder.build("aabb".chars()).outcome(), Ok(()));
+        let rejection = automata_builder
+            .build("aaba".chars())
+            .outcome()
+            .unwrap_err();
+        assert_eq!(
+            rejection.reason(),
+            &RejectionReason::NoMovement {
+                symbol: 'a',
+                top: 'A'
+            }
+        );
+        assert_eq!(rejection.position(), 3);
+        assert_eq!(
+            rejection.configuration(),
+            &Configuration::new("q1", vec!['Z', 'A'], 3)
+        );
+        assert_eq!(
+            rejection.to_string(),
+            "after 3 symbols, in state q1 with stack AZ: \
+             no movement reads a with A on top of the stack"
+        );
+        let rejection = automata_builder.build("abb".chars()).outcome().unwrap_err();
+        assert_eq!(rejection.reason(), &RejectionReason::StackEmptied);
+        assert_eq!(rejection.position(), 2);
+        let rejection = automata_builder.build("aab".chars()).outcome().unwrap_err();
+        assert_eq!(rejection.reason(), &RejectionReason::StackNotEmpty);
+
+        let (outcome, trace) = automata_builder.build("aaba".chars()).outcome_and_trace();
+        assert_eq!(outcome, automata_builder.build("aaba".chars()).outcome());
+        assert_eq!(trace, automata_builder.build("aaba".chars()).trace());
+
+        ruleset.remove(&("q1", None, 'Z'));
+        ruleset.insert(("q1", None, 'Z'), ("q2", vec!['Z']));
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], ruleset)
+            .accepting(Acceptance::FinalState, ["q2"]);
+        let rejection = automata_builder.build("aab".chars()).outcome().unwrap_err();
+        assert_eq!(rejection.reason(), &RejectionReason::NotAccepting);
+        assert_eq!(
+            rejection.configuration(),
+            &Configuration::new("q1", vec!['Z', 'A'], 3)
+        );
+    }
+
+    #[test]
+    fn test_rejection_skips_repeated_configurations() {
+        // Both branches reach (q, b, Z), but only the one through r goes on from there
+        let mut ruleset = Movements::new();
+        ruleset.insert(('p', Some('a'), 'Z'), ('q', vec!['Z']));
+        ruleset.insert(('p', Some('a'), 'Z'), ('s', vec!['Z']));
+        ruleset.insert(('s', None, 'Z'), ('r', vec!['Z']));
+        ruleset.insert(('r', None, 'Z'), ('q', vec!['Z']));
+        let automata_builder = AutomataBuilder::new('p', vec!['Z'], ruleset);
+
+        let (outcome, trace) = automata_builder.build("ab".chars()).outcome_and_trace();
+        let rejection = outcome.unwrap_err();
+        assert_eq!(
+            rejection.reason(),
+            &RejectionReason::NoMovement {
+                symbol: 'b',
+                top: 'Z'
+            }
+        );
+        assert_eq!(
+            rejection.configuration(),
+            &Configuration::new('q', vec!['Z'], 1)
+        );
+        assert_eq!(
+            trace.steps().last().map(|step| step.configuration()),
+            Some(rejection.configuration())
+        );
+    }
 }
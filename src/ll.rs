@@ -0,0 +1,246 @@
+//! LL(1) parse tables, and the deterministic predictive automata built from them
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    grammar::{first_of, Grammar, Lookahead, Production, Symbol},
+    AutomataBuilder, Movements,
+};
+
+/// A state of a predictive automaton
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LlState<T> {
+    /// About to read the next input symbol
+    Read,
+    /// Has read this symbol, and expands nonterminals until it matches it
+    Look(Lookahead<T>),
+}
+
+pub type LlAutomata<T, N> = AutomataBuilder<
+    Symbol<Lookahead<T>, N>,
+    LlState<T>,
+    Movements<Lookahead<T>, Symbol<Lookahead<T>, N>, LlState<T>>,
+>;
+
+/// The nonterminal and lookahead of a cell of the parse table
+type Cell<T, N> = (N, Lookahead<T>);
+
+/// A cell of the parse table with more than one production
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlConflict<T, N> {
+    pub nonterminal: N,
+    pub lookahead: Lookahead<T>,
+    pub productions: Vec<Production<T, N>>,
+}
+
+/// The LL(1) parse table of a grammar, giving the productions to expand a nonterminal by
+/// when it is on top of the stack and the next input symbol is the lookahead
+#[derive(Debug, Clone)]
+pub struct LlTable<T, N> {
+    start: N,
+    nonterminals: Vec<N>,
+    lookaheads: Vec<Lookahead<T>>,
+    cells: HashMap<Cell<T, N>, Vec<Production<T, N>>>,
+}
+
+impl<T, N> LlTable<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Hash + Eq,
+{
+    /// A production goes in the cells of the terminals that can start its body, and if its
+    /// body can derive the empty word, in the cells of what can follow its head
+    pub fn new(grammar: &Grammar<T, N>) -> Self {
+        let nullable = grammar.nullable();
+        let first = grammar.first();
+        let follow = grammar.follow();
+        let mut cells: HashMap<_, Vec<_>> = HashMap::new();
+        for production in grammar.productions() {
+            let (terminals, body_nullable) = first_of(&first, &nullable, &production.body);
+            let mut lookaheads = terminals
+                .into_iter()
+                .map(Lookahead::Terminal)
+                .collect::<HashSet<_>>();
+            if body_nullable {
+                lookaheads.extend(follow[&production.head].iter().cloned());
+            }
+            for lookahead in lookaheads {
+                let cell = cells
+                    .entry((production.head.clone(), lookahead))
+                    .or_default();
+                if !cell.contains(production) {
+                    cell.push(production.clone());
+                }
+            }
+        }
+        Self {
+            start: grammar.start().clone(),
+            nonterminals: grammar.nonterminals().to_vec(),
+            lookaheads: grammar
+                .terminals()
+                .iter()
+                .cloned()
+                .map(Lookahead::Terminal)
+                .chain(std::iter::once(Lookahead::End))
+                .collect(),
+            cells,
+        }
+    }
+
+    /// The productions in the cell of `nonterminal` and `lookahead`, in grammar order
+    pub fn get(&self, nonterminal: &N, lookahead: &Lookahead<T>) -> &[Production<T, N>] {
+        self.cells
+            .get(&(nonterminal.clone(), lookahead.clone()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Every cell with more than one production, by nonterminal and then lookahead in
+    /// grammar order
+    pub fn conflicts(&self) -> Vec<LlConflict<T, N>> {
+        let mut conflicts = Vec::new();
+        for nonterminal in &self.nonterminals {
+            for lookahead in &self.lookaheads {
+                let productions = self.get(nonterminal, lookahead);
+                if productions.len() > 1 {
+                    conflicts.push(LlConflict {
+                        nonterminal: nonterminal.clone(),
+                        lookahead: lookahead.clone(),
+                        productions: productions.to_vec(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Whether the grammar is LL(1), that is, no cell has more than one production
+    pub fn is_ll1(&self) -> bool {
+        self.cells.values().all(|cell| cell.len() <= 1)
+    }
+
+    /// Builds the predictive automaton for the table, which accepts by empty stack the
+    /// words of the grammar followed by [`Lookahead::End`], see [`Lookahead::terminate`].
+    ///
+    /// The stack starts with the start symbol above the end marker. A nonterminal on top
+    /// of the stack is only expanded after reading the next symbol, which the automaton
+    /// keeps in its state until a terminal matches it. Without conflicts it is
+    /// deterministic, and otherwise it follows every production of a cell.
+    pub fn automaton(&self) -> LlAutomata<T, N> {
+        let mut movements = Movements::new();
+        for lookahead in &self.lookaheads {
+            let terminal = Symbol::Terminal(lookahead.clone());
+            let look = LlState::Look(lookahead.clone());
+            movements.insert(
+                (LlState::Read, Some(lookahead.clone()), terminal.clone()),
+                (LlState::Read, vec![]),
+            );
+            movements.insert((look.clone(), None, terminal), (LlState::Read, vec![]));
+            for nonterminal in &self.nonterminals {
+                let productions = self.get(nonterminal, lookahead);
+                if productions.is_empty() {
+                    continue;
+                }
+                let top = Symbol::Nonterminal(nonterminal.clone());
+                movements.insert(
+                    (LlState::Read, Some(lookahead.clone()), top.clone()),
+                    (look.clone(), vec![top.clone()]),
+                );
+                for production in productions {
+                    let body = production
+                        .body
+                        .iter()
+                        .map(|symbol| match symbol {
+                            Symbol::Terminal(t) => Symbol::Terminal(Lookahead::Terminal(t.clone())),
+                            Symbol::Nonterminal(n) => Symbol::Nonterminal(n.clone()),
+                        })
+                        .collect();
+                    movements.insert((look.clone(), None, top.clone()), (look.clone(), body));
+                }
+            }
+        }
+        AutomataBuilder::new(
+            LlState::Read,
+            vec![
+                Symbol::Terminal(Lookahead::End),
+                Symbol::Nonterminal(self.start.clone()),
+            ],
+            movements,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        analysis::is_deterministic,
+        fixtures::{expressions_ll1, sums},
+        grammar::{Lookahead, Production},
+    };
+
+    use super::LlTable;
+
+    #[test]
+    fn test_first_and_follow() {
+        let grammar = expressions_ll1();
+        let first = grammar.first();
+        assert_eq!(first[&'E'], HashSet::from(['(', 'n']));
+        assert_eq!(first[&'A'], HashSet::from(['+']));
+        let follow = grammar.follow();
+        assert_eq!(
+            follow[&'A'],
+            HashSet::from([Lookahead::Terminal(')'), Lookahead::End])
+        );
+        assert_eq!(
+            follow[&'F'],
+            HashSet::from([
+                Lookahead::Terminal('*'),
+                Lookahead::Terminal('+'),
+                Lookahead::Terminal(')'),
+                Lookahead::End
+            ])
+        );
+    }
+
+    #[test]
+    fn test_ll1_automaton() {
+        let grammar = expressions_ll1();
+        let table = LlTable::new(&grammar);
+        assert!(table.is_ll1());
+        assert_eq!(
+            table.get(&'B', &Lookahead::Terminal('+')),
+            [Production::new('B', [])]
+        );
+        assert_eq!(table.get(&'F', &Lookahead::Terminal('+')), []);
+
+        let automata_builder = table.automaton();
+        assert!(is_deterministic(automata_builder.movements()));
+        for word in ["n", "n+n*n", "(n+n)*n", "((n))", "", "n+", "(n", "n)", "nn"] {
+            assert_eq!(
+                automata_builder
+                    .build(Lookahead::terminate(word.chars()))
+                    .complete(),
+                grammar.automaton().build(word.chars()).complete(),
+                "{word}"
+            );
+        }
+    }
+
+    #[test]
+    /// Test for sums, E -> E+n | n, which is left recursive
+    fn test_conflicts() {
+        let grammar = sums();
+        let table = LlTable::new(&grammar);
+        assert!(!table.is_ll1());
+        let conflicts = table.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].nonterminal, 'E');
+        assert_eq!(conflicts[0].lookahead, Lookahead::Terminal('n'));
+        assert_eq!(conflicts[0].productions, grammar.productions());
+        assert!(!is_deterministic(table.automaton().movements()));
+    }
+}
@@ -0,0 +1,755 @@
+//! LR item automata, LR(0), SLR(1) and LALR(1) parse tables, and a shift-reduce driver
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    hash::Hash,
+};
+
+use crate::{
+    grammar::{first_of, Grammar, Lookahead, ParseTree, Production, Symbol},
+    Stack,
+};
+
+/// A production with a dot before its `dot`-th symbol. The production is an index into
+/// the productions of the grammar, or `None` for the augmented production `S' -> S`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LrItem {
+    pub production: Option<usize>,
+    pub dot: usize,
+}
+
+/// The canonical collection of LR(0) item sets of a grammar, with the transitions between
+/// them
+#[derive(Debug, Clone)]
+pub struct LrAutomaton<T, N> {
+    grammar: Grammar<T, N>,
+    /// The body of the augmented production
+    start: Symbol<T, N>,
+    states: Vec<Vec<LrItem>>,
+    transitions: HashMap<(usize, Symbol<T, N>), usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LrKind {
+    /// Reduces on every lookahead
+    Lr0,
+    /// Reduces on the lookaheads that can follow the head of the production
+    Slr1,
+    /// Reduces on the lookaheads that can follow the item in the canonical LR(1)
+    /// automaton, merging the states with the same items
+    Lalr1,
+}
+
+/// An entry of the action table. Shifts come first, then accepting, then reductions in
+/// grammar order, and the driver picks the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    /// Consumes the lookahead and goes to this state
+    Shift(usize),
+    Accept,
+    /// Reduces by this production of the grammar
+    Reduce(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LrConflictKind {
+    ShiftReduce,
+    ReduceReduce,
+}
+
+/// A cell of the action table with more than one action, along with the items of the
+/// state that call for them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LrConflict<T> {
+    pub kind: LrConflictKind,
+    pub state: usize,
+    pub lookahead: Lookahead<T>,
+    pub actions: Vec<Action>,
+    pub items: Vec<LrItem>,
+}
+
+/// A word rejected by the driver, which found no action for its state and lookahead, or
+/// whose reductions would have gone on forever without shifting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LrError<T> {
+    /// Number of input symbols shifted before the error
+    pub position: usize,
+    pub state: usize,
+    pub lookahead: Lookahead<T>,
+    /// Whether the driver stopped reducing because it kept coming back to the same state
+    /// without touching the rest of the stack
+    pub diverged: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct LrTable<T, N> {
+    automaton: LrAutomaton<T, N>,
+    actions: HashMap<(usize, Lookahead<T>), Vec<Action>>,
+}
+
+impl<T, N> LrAutomaton<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Hash + Eq,
+{
+    /// Builds the item sets of the grammar augmented with a new start production, after
+    /// removing its useless symbols
+    pub fn new(grammar: &Grammar<T, N>) -> Self {
+        let mut automaton = Self {
+            grammar: grammar.remove_useless(),
+            start: Symbol::Nonterminal(grammar.start().clone()),
+            states: Vec::new(),
+            transitions: HashMap::new(),
+        };
+        let start = vec![LrItem {
+            production: None,
+            dot: 0,
+        }];
+        let mut kernels = HashMap::from([(start.clone(), 0)]);
+        automaton.states.push(automaton.closure(start));
+        let mut state = 0;
+        while state < automaton.states.len() {
+            // Successor kernels by symbol, in the order the symbols first appear
+            let mut successors: Vec<(Symbol<T, N>, Vec<LrItem>)> = Vec::new();
+            for &item in &automaton.states[state] {
+                let Some(symbol) = automaton.next_symbol(item) else {
+                    continue;
+                };
+                let advanced = LrItem {
+                    dot: item.dot + 1,
+                    ..item
+                };
+                match successors.iter_mut().find(|(s, _)| s == symbol) {
+                    Some((_, kernel)) => kernel.push(advanced),
+                    None => successors.push((symbol.clone(), vec![advanced])),
+                }
+            }
+            for (symbol, mut kernel) in successors {
+                kernel.sort();
+                kernel.dedup();
+                let target = match kernels.get(&kernel) {
+                    Some(&target) => target,
+                    None => {
+                        let target = automaton.states.len();
+                        kernels.insert(kernel.clone(), target);
+                        let closure = automaton.closure(kernel);
+                        automaton.states.push(closure);
+                        target
+                    }
+                };
+                automaton.transitions.insert((state, symbol), target);
+            }
+            state += 1;
+        }
+        automaton
+    }
+
+    fn body(&self, production: Option<usize>) -> &[Symbol<T, N>] {
+        match production {
+            Some(p) => &self.grammar.productions()[p].body,
+            None => std::slice::from_ref(&self.start),
+        }
+    }
+
+    /// The symbol right after the dot, if the dot is not at the end
+    fn next_symbol(&self, item: LrItem) -> Option<&Symbol<T, N>> {
+        self.body(item.production).get(item.dot)
+    }
+
+    /// The kernel items along with the items of every production of a nonterminal right
+    /// after a dot, sorted
+    fn closure(&self, kernel: Vec<LrItem>) -> Vec<LrItem> {
+        let mut items = kernel;
+        let mut i = 0;
+        while i < items.len() {
+            if let Some(Symbol::Nonterminal(n)) = self.next_symbol(items[i]) {
+                for (p, production) in self.grammar.productions().iter().enumerate() {
+                    let item = LrItem {
+                        production: Some(p),
+                        dot: 0,
+                    };
+                    if &production.head == n && !items.contains(&item) {
+                        items.push(item);
+                    }
+                }
+            }
+            i += 1;
+        }
+        items.sort();
+        items
+    }
+
+    /// The grammar without its useless symbols, whose productions [`Action::Reduce`] refers
+    /// to
+    pub fn grammar(&self) -> &Grammar<T, N> {
+        &self.grammar
+    }
+
+    /// The item sets, the first one being the initial state
+    pub fn states(&self) -> &[Vec<LrItem>] {
+        &self.states
+    }
+
+    pub fn transition(&self, state: usize, symbol: &Symbol<T, N>) -> Option<usize> {
+        self.transitions.get(&(state, symbol.clone())).copied()
+    }
+
+    /// Writes `item` as a production with a dot, such as `E -> E + . T`. The augmented
+    /// start symbol is written with a prime.
+    pub fn item_to_string(&self, item: LrItem) -> String
+    where
+        T: Display,
+        N: Display,
+    {
+        let head = match item.production {
+            Some(p) => self.grammar.productions()[p].head.to_string(),
+            None => format!("{}'", self.grammar.start()),
+        };
+        let mut symbols = self
+            .body(item.production)
+            .iter()
+            .map(|symbol| match symbol {
+                Symbol::Terminal(t) => t.to_string(),
+                Symbol::Nonterminal(n) => n.to_string(),
+            })
+            .collect::<Vec<_>>();
+        symbols.insert(item.dot, ".".to_string());
+        format!("{head} -> {}", symbols.join(" "))
+    }
+
+    /// Builds the action table of the given kind
+    pub fn table(&self, kind: LrKind) -> LrTable<T, N> {
+        let lookaheads = self.lookaheads();
+        let follow = self.grammar.follow();
+        let mut actions: HashMap<_, Vec<_>> = HashMap::new();
+        let mut add = |state: usize, lookahead: Lookahead<T>, action: Action| {
+            let cell = actions.entry((state, lookahead)).or_default();
+            if !cell.contains(&action) {
+                cell.push(action);
+            }
+        };
+
+        for (state, items) in self.states.iter().enumerate() {
+            for &item in items {
+                match (self.next_symbol(item), item.production) {
+                    (Some(Symbol::Terminal(t)), _) => {
+                        let target = self.transitions[&(state, Symbol::Terminal(t.clone()))];
+                        add(state, Lookahead::Terminal(t.clone()), Action::Shift(target));
+                    }
+                    (Some(Symbol::Nonterminal(_)), _) => {}
+                    (None, None) => add(state, Lookahead::End, Action::Accept),
+                    (None, Some(p)) => match kind {
+                        LrKind::Lr0 => {
+                            for lookahead in &lookaheads {
+                                add(state, lookahead.clone(), Action::Reduce(p));
+                            }
+                        }
+                        LrKind::Slr1 => {
+                            let head = &self.grammar.productions()[p].head;
+                            for lookahead in follow[head].iter() {
+                                add(state, lookahead.clone(), Action::Reduce(p));
+                            }
+                        }
+                        LrKind::Lalr1 => {}
+                    },
+                }
+            }
+        }
+        if kind == LrKind::Lalr1 {
+            for (state, item, lookahead) in self.lalr_reductions() {
+                if let Some(p) = item.production {
+                    add(state, lookahead, Action::Reduce(p));
+                }
+            }
+        }
+        for cell in actions.values_mut() {
+            cell.sort();
+        }
+        LrTable {
+            automaton: self.clone(),
+            actions,
+        }
+    }
+
+    /// The terminals of the grammar in order, and then the end of the input
+    fn lookaheads(&self) -> Vec<Lookahead<T>> {
+        self.grammar
+            .terminals()
+            .iter()
+            .cloned()
+            .map(Lookahead::Terminal)
+            .chain(std::iter::once(Lookahead::End))
+            .collect()
+    }
+
+    /// The LR(1) closure of items with lookaheads, where a `None` lookahead stands for any
+    /// lookahead the items may later get
+    fn closure1(
+        &self,
+        first: &HashMap<N, HashSet<T>>,
+        nullable: &HashSet<N>,
+        kernel: Vec<(LrItem, Option<Lookahead<T>>)>,
+    ) -> Vec<(LrItem, Option<Lookahead<T>>)> {
+        let mut items = kernel;
+        let mut seen = items.iter().cloned().collect::<HashSet<_>>();
+        let mut i = 0;
+        while i < items.len() {
+            let (item, lookahead) = items[i].clone();
+            i += 1;
+            let Some(Symbol::Nonterminal(n)) = self.next_symbol(item) else {
+                continue;
+            };
+            let rest = &self.body(item.production)[item.dot + 1..];
+            let (terminals, rest_nullable) = first_of(first, nullable, rest);
+            let mut lookaheads = terminals
+                .into_iter()
+                .map(|t| Some(Lookahead::Terminal(t)))
+                .collect::<Vec<_>>();
+            if rest_nullable {
+                lookaheads.push(lookahead);
+            }
+            for (p, production) in self.grammar.productions().iter().enumerate() {
+                if &production.head != n {
+                    continue;
+                }
+                for lookahead in &lookaheads {
+                    let entry = (
+                        LrItem {
+                            production: Some(p),
+                            dot: 0,
+                        },
+                        lookahead.clone(),
+                    );
+                    if seen.insert(entry.clone()) {
+                        items.push(entry);
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    /// The complete items of every state with each of their LALR(1) lookaheads, found by
+    /// propagating lookaheads between kernel items
+    fn lalr_reductions(&self) -> Vec<(usize, LrItem, Lookahead<T>)> {
+        let first = self.grammar.first();
+        let nullable = self.grammar.nullable();
+        let is_kernel = |item: &LrItem| item.dot > 0 || item.production.is_none();
+
+        let mut lookaheads: HashMap<(usize, LrItem), HashSet<Lookahead<T>>> = HashMap::new();
+        let mut propagation: Vec<((usize, LrItem), (usize, LrItem))> = Vec::new();
+        lookaheads.insert(
+            (
+                0,
+                LrItem {
+                    production: None,
+                    dot: 0,
+                },
+            ),
+            HashSet::from([Lookahead::End]),
+        );
+        for (state, items) in self.states.iter().enumerate() {
+            for &kernel in items.iter().filter(|item| is_kernel(item)) {
+                for (item, lookahead) in self.closure1(&first, &nullable, vec![(kernel, None)]) {
+                    let Some(symbol) = self.next_symbol(item) else {
+                        continue;
+                    };
+                    let target = (
+                        self.transitions[&(state, symbol.clone())],
+                        LrItem {
+                            dot: item.dot + 1,
+                            ..item
+                        },
+                    );
+                    match lookahead {
+                        Some(lookahead) => {
+                            lookaheads.entry(target).or_default().insert(lookahead);
+                        }
+                        None => propagation.push(((state, kernel), target)),
+                    }
+                }
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (from, to) in &propagation {
+                let new = lookaheads.get(from).cloned().unwrap_or_default();
+                let entry = lookaheads.entry(*to).or_default();
+                for lookahead in new {
+                    changed |= entry.insert(lookahead);
+                }
+            }
+        }
+
+        let mut reductions = Vec::new();
+        for (state, items) in self.states.iter().enumerate() {
+            let kernel = items
+                .iter()
+                .filter(|item| is_kernel(item))
+                .flat_map(|&item| {
+                    lookaheads
+                        .get(&(state, item))
+                        .into_iter()
+                        .flatten()
+                        .map(move |lookahead| (item, Some(lookahead.clone())))
+                })
+                .collect();
+            for (item, lookahead) in self.closure1(&first, &nullable, kernel) {
+                if let (None, Some(lookahead)) = (self.next_symbol(item), lookahead) {
+                    reductions.push((state, item, lookahead));
+                }
+            }
+        }
+        reductions
+    }
+}
+
+impl<T, N> LrTable<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Hash + Eq,
+{
+    pub fn automaton(&self) -> &LrAutomaton<T, N> {
+        &self.automaton
+    }
+
+    /// The actions for `state` and `lookahead`, in order
+    pub fn action(&self, state: usize, lookahead: &Lookahead<T>) -> &[Action] {
+        self.actions
+            .get(&(state, lookahead.clone()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The state to go to from `state` after reducing to `nonterminal`
+    pub fn goto(&self, state: usize, nonterminal: &N) -> Option<usize> {
+        self.automaton
+            .transition(state, &Symbol::Nonterminal(nonterminal.clone()))
+    }
+
+    /// Every cell with more than one action, by state and then lookahead in grammar order
+    pub fn conflicts(&self) -> Vec<LrConflict<T>> {
+        let mut conflicts = Vec::new();
+        for (state, items) in self.automaton.states.iter().enumerate() {
+            for lookahead in self.automaton.lookaheads() {
+                let actions = self.action(state, &lookahead);
+                if actions.len() < 2 {
+                    continue;
+                }
+                let kind = match actions.iter().any(|a| matches!(a, Action::Shift(_))) {
+                    true => LrConflictKind::ShiftReduce,
+                    false => LrConflictKind::ReduceReduce,
+                };
+                let items = items
+                    .iter()
+                    .copied()
+                    .filter(|&item| match self.automaton.next_symbol(item) {
+                        Some(Symbol::Terminal(t)) => lookahead == Lookahead::Terminal(t.clone()),
+                        Some(Symbol::Nonterminal(_)) => false,
+                        None => actions
+                            .iter()
+                            .any(|action| match (action, item.production) {
+                                (Action::Reduce(p), Some(q)) => *p == q,
+                                (Action::Accept, None) => true,
+                                _ => false,
+                            }),
+                    })
+                    .collect();
+                conflicts.push(LrConflict {
+                    kind,
+                    state,
+                    lookahead,
+                    actions: actions.to_vec(),
+                    items,
+                });
+            }
+        }
+        conflicts
+    }
+
+    /// Parses `word` by shifting and reducing, taking the first action of every cell, and
+    /// returns the parse tree of the start symbol.
+    ///
+    /// With conflicts, the first action may reduce forever without shifting, by a cycle of
+    /// unit productions or by piling up empty ones. The driver stops with a diverged error
+    /// when a reduction goes back to a state that is still on top of an untouched part of
+    /// the stack since the last shift, as the same reductions would follow again.
+    pub fn parse<W>(&self, word: W) -> Result<ParseTree<T, N>, LrError<T>>
+    where
+        W: IntoIterator<Item = T>,
+    {
+        let productions = self.automaton.grammar.productions();
+        let mut input = Lookahead::terminate(word).peekable();
+        let mut position = 0;
+        let mut states = Stack::new(vec![0]);
+        let mut trees: Stack<ParseTree<T, N>> = Stack::new(vec![]);
+        // The height of the stack when each state was last pushed since the last shift,
+        // for the entries that have not been popped since
+        let mut pushed = HashMap::from([(0, 1)]);
+        loop {
+            let state = *states.peek().expect("the initial state is never popped");
+            let lookahead = input.peek().cloned().unwrap_or(Lookahead::End);
+            let error = || LrError {
+                position,
+                state,
+                lookahead: lookahead.clone(),
+                diverged: false,
+            };
+            match self.action(state, &lookahead).first().ok_or_else(error)? {
+                Action::Shift(target) => {
+                    if let Some(Lookahead::Terminal(t)) = input.next() {
+                        trees.push(ParseTree::Leaf(t));
+                    }
+                    states.push(*target);
+                    position += 1;
+                    pushed = HashMap::from([(*target, states.len())]);
+                }
+                Action::Reduce(p) => {
+                    let Production { head, body } = &productions[*p];
+                    let mut children = (0..body.len())
+                        .filter_map(|_| {
+                            states.pop();
+                            trees.pop()
+                        })
+                        .collect::<Vec<_>>();
+                    children.reverse();
+                    let state = *states.peek().expect("the initial state is never popped");
+                    let target = self.goto(state, head).ok_or_else(error)?;
+                    let height = states.len();
+                    if pushed.get(&target).is_some_and(|&h| h <= height + 1) {
+                        return Err(LrError {
+                            diverged: true,
+                            ..error()
+                        });
+                    }
+                    pushed.retain(|_, h| *h <= height);
+                    pushed.insert(target, height + 1);
+                    states.push(target);
+                    trees.push(ParseTree::Node {
+                        head: head.clone(),
+                        children,
+                    });
+                }
+                Action::Accept => return trees.pop().ok_or_else(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        earley,
+        fixtures::{expressions, n, t},
+        grammar::{Grammar, Lookahead, Production},
+    };
+
+    use super::{Action, LrAutomaton, LrConflictKind, LrError, LrKind};
+
+    #[test]
+    fn test_expressions() {
+        let grammar = expressions();
+        let automaton = LrAutomaton::new(&grammar);
+        assert_eq!(automaton.states().len(), 12);
+        assert_eq!(
+            automaton.item_to_string(automaton.states()[0][0]),
+            "E' -> . E"
+        );
+
+        let lr0 = automaton.table(LrKind::Lr0);
+        let conflicts = lr0.conflicts();
+        assert!(!conflicts.is_empty());
+        assert!(conflicts
+            .iter()
+            .all(|c| c.kind == LrConflictKind::ShiftReduce));
+        let items = conflicts
+            .iter()
+            .flat_map(|c| &c.items)
+            .map(|&item| automaton.item_to_string(item))
+            .collect::<Vec<_>>();
+        assert!(items.contains(&"E -> T .".to_string()));
+        assert!(items.contains(&"T -> T . * F".to_string()));
+
+        for kind in [LrKind::Slr1, LrKind::Lalr1] {
+            let table = automaton.table(kind);
+            assert_eq!(table.conflicts(), []);
+            let tree = table.parse("n+n*n".chars()).unwrap();
+            assert_eq!(tree.to_string(), "E(E(T(F(n))) + T(T(F(n)) * F(n)))");
+            assert_eq!(
+                table.parse("(n)".chars()).unwrap().to_string(),
+                "E(T(F(( E(T(F(n))) ))))"
+            );
+            let error = table.parse("n+".chars()).unwrap_err();
+            assert_eq!(error.position, 2);
+            assert_eq!(error.lookahead, Lookahead::End);
+            assert!(matches!(
+                table.parse("n)".chars()),
+                Err(LrError { position: 1, .. })
+            ));
+            for word in ["n", "n*(n+n)", "((n))*n+n", "", "+", "(n", "nn"] {
+                assert_eq!(
+                    table.parse(word.chars()).is_ok(),
+                    earley::parse(&grammar, &word.chars().collect::<Vec<_>>()).accepted(),
+                    "{word}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    /// Test for S -> L=R | R, L -> *R | i, R -> L, which is LALR(1) but not SLR(1)
+    fn test_lalr_but_not_slr() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('L'), t('='), n('R')]),
+                Production::new('S', [n('R')]),
+                Production::new('L', [t('*'), n('R')]),
+                Production::new('L', [t('i')]),
+                Production::new('R', [n('L')]),
+            ],
+        );
+        let automaton = LrAutomaton::new(&grammar);
+
+        let conflicts = automaton.table(LrKind::Slr1).conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, LrConflictKind::ShiftReduce);
+        assert_eq!(conflicts[0].lookahead, Lookahead::Terminal('='));
+        assert!(matches!(
+            conflicts[0].actions[..],
+            [Action::Shift(_), Action::Reduce(4)]
+        ));
+        let items = conflicts[0]
+            .items
+            .iter()
+            .map(|&item| automaton.item_to_string(item))
+            .collect::<Vec<_>>();
+        assert_eq!(items, ["S -> L . = R", "R -> L ."]);
+
+        let lalr = automaton.table(LrKind::Lalr1);
+        assert_eq!(lalr.conflicts(), []);
+        assert_eq!(
+            lalr.parse("*i=i".chars()).unwrap().to_string(),
+            "S(L(* R(L(i))) = R(L(i)))"
+        );
+        assert!(lalr.parse("i=".chars()).is_err());
+    }
+
+    #[test]
+    /// Test for S -> A | B, A -> a, B -> a, and for S -> AS | ε, A -> a
+    fn test_reduce_reduce_and_empty() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('A')]),
+                Production::new('S', [n('B')]),
+                Production::new('A', [t('a')]),
+                Production::new('B', [t('a')]),
+            ],
+        );
+        let conflicts = LrAutomaton::new(&grammar).table(LrKind::Lalr1).conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, LrConflictKind::ReduceReduce);
+        assert_eq!(conflicts[0].actions, [Action::Reduce(2), Action::Reduce(3)]);
+
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('A'), n('S')]),
+                Production::new('S', []),
+                Production::new('A', [t('a')]),
+            ],
+        );
+        let table = LrAutomaton::new(&grammar).table(LrKind::Lalr1);
+        assert_eq!(table.conflicts(), []);
+        assert_eq!(table.parse("".chars()).unwrap().to_string(), "S(ε)");
+        assert_eq!(
+            table.parse("aa".chars()).unwrap().to_string(),
+            "S(A(a) S(A(a) S(ε)))"
+        );
+    }
+
+    #[test]
+    /// Test for S -> S | a, where accepting and reducing by S -> S share a cell
+    fn test_accept_before_reduce() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('S')]),
+                Production::new('S', [t('a')]),
+            ],
+        );
+        let table = LrAutomaton::new(&grammar).table(LrKind::Lalr1);
+        let conflicts = table.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].actions, [Action::Accept, Action::Reduce(0)]);
+        assert_eq!(table.parse("a".chars()).unwrap().to_string(), "S(a)");
+        assert!(table.parse("aa".chars()).is_err());
+    }
+
+    #[test]
+    /// Test for B -> ε, S -> BSB, A -> aa, where S derives no word
+    fn test_useless_symbols() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('B', []),
+                Production::new('S', [n('B'), n('S'), n('B')]),
+                Production::new('A', [t('a'), t('a')]),
+            ],
+        );
+        let automaton = LrAutomaton::new(&grammar);
+        assert!(automaton.grammar().productions().is_empty());
+        for kind in [LrKind::Lr0, LrKind::Slr1, LrKind::Lalr1] {
+            let table = automaton.table(kind);
+            assert_eq!(table.conflicts(), []);
+            assert_eq!(
+                table.parse("".chars()),
+                Err(LrError {
+                    position: 0,
+                    state: 0,
+                    lookahead: Lookahead::End,
+                    diverged: false,
+                })
+            );
+            assert!(table.parse("aa".chars()).is_err());
+        }
+    }
+
+    #[test]
+    /// Test for A -> A | a, S -> A, where the first action of a cell reduces forever, and
+    /// for S -> BS | a, B -> ε with its reductions put before its shifts
+    fn test_diverging_reductions() {
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('A', [n('A')]),
+                Production::new('S', [n('A')]),
+                Production::new('A', [t('a')]),
+            ],
+        );
+        let table = LrAutomaton::new(&grammar).table(LrKind::Lalr1);
+        assert_eq!(table.conflicts()[0].kind, LrConflictKind::ReduceReduce);
+        let error = table.parse("a".chars()).unwrap_err();
+        assert!(error.diverged);
+        assert_eq!(error.position, 1);
+
+        let grammar = Grammar::new(
+            'S',
+            [
+                Production::new('S', [n('B'), n('S')]),
+                Production::new('S', [t('a')]),
+                Production::new('B', []),
+            ],
+        );
+        let automaton = LrAutomaton::new(&grammar);
+        let mut table = automaton.table(LrKind::Lr0);
+        assert!(table.parse("a".chars()).is_ok());
+        // Reduce by B -> ε before shifting
+        for cell in table.actions.values_mut() {
+            cell.reverse();
+        }
+        assert!(table.parse("a".chars()).unwrap_err().diverged);
+    }
+}
@@ -1,3 +1,118 @@
-fn main() {
-    println!("Hello, world!");
+use std::{
+    env, fs,
+    io::{self, BufRead},
+    process::ExitCode,
+};
+
+use stack_automata::{format, Budget};
+
+const USAGE: &str =
+    "usage: stack_automata [--trace] [--max-steps N] [--max-depth N] [--] <definition> [word...]
+
+Runs every word given as an argument, or every line of stdin when there are none,
+through the automaton defined in <definition>. Arguments after -- are never read as
+options. The stack depth is bounded by 1024 unless --max-depth is given. Exits with 1
+if any word is rejected.";
+
+struct Options {
+    trace: bool,
+    budget: Budget,
+    definition: String,
+    words: Option<Vec<String>>,
+}
+
+fn options() -> Result<Options, String> {
+    let mut trace = false;
+    let mut budget = Budget::default();
+    let mut positional = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--" => {
+                positional.extend(args);
+                break;
+            }
+            "--trace" => trace = true,
+            "--max-steps" | "--max-depth" => {
+                let n = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or(format!("{arg} expects a number"))?;
+                budget = match arg.as_str() {
+                    "--max-steps" => budget.max_steps(n),
+                    _ => budget.max_stack_depth(n),
+                };
+            }
+            "-h" | "--help" => return Err(String::new()),
+            _ => positional.push(arg),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let definition = positional.next().ok_or("missing definition file")?;
+    let words = positional.collect::<Vec<_>>();
+    Ok(Options {
+        trace,
+        budget,
+        definition,
+        words: (!words.is_empty()).then_some(words),
+    })
+}
+
+fn main() -> ExitCode {
+    let options = match options() {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{message}");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let automata_builder = match fs::read_to_string(&options.definition)
+        .map_err(|e| e.to_string())
+        .and_then(|text| format::parse(&text).map_err(|e| e.to_string()))
+    {
+        Ok(automata_builder) => automata_builder.budget(options.budget),
+        Err(message) => {
+            eprintln!("{}: {message}", options.definition);
+            return ExitCode::from(2);
+        }
+    };
+
+    let words: Box<dyn Iterator<Item = io::Result<String>>> = match options.words {
+        Some(words) => Box::new(words.into_iter().map(Ok)),
+        None => Box::new(io::stdin().lock().lines()),
+    };
+    let mut all_accepted = true;
+    for word in words {
+        let word = match word {
+            Ok(word) => word,
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::from(2);
+            }
+        };
+        let automata = automata_builder.build(word.chars());
+        let (outcome, trace) = if options.trace {
+            let (outcome, trace) = automata.outcome_and_trace();
+            (outcome, Some(trace))
+        } else {
+            (automata.outcome(), None)
+        };
+        let shown = if word.is_empty() { "ε" } else { &word };
+        match &outcome {
+            Ok(()) => println!("accept {shown}"),
+            Err(rejection) => println!("reject {shown}: {rejection}"),
+        }
+        if let Some(trace) = trace {
+            println!("{trace}\n");
+        }
+        all_accepted &= outcome.is_ok();
+    }
+    if all_accepted {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
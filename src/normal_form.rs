@@ -0,0 +1,514 @@
+//! Normal forms of context-free grammars.
+//!
+//! Transformations that need new nonterminals take them from [`FreshNonterminal`] and
+//! report them in [`Normalized::introduced`].
+
+use std::{collections::HashSet, hash::Hash};
+
+use crate::{
+    grammar::{Grammar, GrammarAutomata, Production, Symbol},
+    AutomataBuilder, Movements,
+};
+
+/// Nonterminals that can make up new nonterminals for a grammar
+pub trait FreshNonterminal: Clone + Hash + Eq {
+    /// A nonterminal that is not in `taken`, named after `base` where possible
+    fn fresh(base: &Self, taken: &HashSet<Self>) -> Self;
+}
+
+/// Adds primes to `base`, as in `S'`
+impl FreshNonterminal for String {
+    fn fresh(base: &Self, taken: &HashSet<Self>) -> Self {
+        let mut fresh = base.clone();
+        while taken.contains(&fresh) {
+            fresh.push('\'');
+        }
+        fresh
+    }
+}
+
+/// Takes the first free capital letter, and then characters beyond ASCII
+impl FreshNonterminal for char {
+    fn fresh(_: &Self, taken: &HashSet<Self>) -> Self {
+        ('A'..='Z')
+            .chain((0x100..).filter_map(char::from_u32))
+            .find(|c| !taken.contains(c))
+            .expect("ran out of characters")
+    }
+}
+
+/// Takes the number after the largest one taken
+impl FreshNonterminal for usize {
+    fn fresh(_: &Self, taken: &HashSet<Self>) -> Self {
+        taken.iter().max().map_or(0, |max| max + 1)
+    }
+}
+
+/// A transformed grammar, along with the nonterminals the transformation introduced in
+/// the order it introduced them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Normalized<T, N> {
+    pub grammar: Grammar<T, N>,
+    pub introduced: Vec<N>,
+}
+
+/// Hands out fresh nonterminals, remembering every one taken
+struct Names<N> {
+    taken: HashSet<N>,
+    introduced: Vec<N>,
+}
+
+impl<N: FreshNonterminal> Names<N> {
+    fn new<T>(grammar: &Grammar<T, N>) -> Self {
+        Self {
+            taken: grammar.nonterminals().iter().cloned().collect(),
+            introduced: Vec::new(),
+        }
+    }
+
+    fn fresh(&mut self, base: &N) -> N {
+        let fresh = N::fresh(base, &self.taken);
+        self.taken.insert(fresh.clone());
+        self.introduced.push(fresh.clone());
+        fresh
+    }
+}
+
+type Body<T, N> = Vec<Symbol<T, N>>;
+
+impl<T, N> Grammar<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: FreshNonterminal,
+{
+    /// Removes every production with an empty body, adding the bodies that leave out any
+    /// nullable nonterminals instead.
+    ///
+    /// If the language has the empty word, the start symbol keeps an empty production. When
+    /// the start symbol also appears in some body, a new start symbol is introduced, with a
+    /// production to the old one and the empty production.
+    pub fn remove_epsilon(&self) -> Normalized<T, N> {
+        let nullable = self.nullable();
+        let mut productions = Vec::new();
+        for production in self.productions() {
+            let mut bodies: Vec<Body<T, N>> = vec![vec![]];
+            for symbol in &production.body {
+                let skippable = matches!(symbol, Symbol::Nonterminal(n) if nullable.contains(n));
+                let skipped = if skippable { bodies.clone() } else { vec![] };
+                for body in &mut bodies {
+                    body.push(symbol.clone());
+                }
+                bodies.extend(skipped);
+            }
+            productions.extend(
+                bodies
+                    .into_iter()
+                    .filter(|body| !body.is_empty())
+                    .map(|body| Production::new(production.head.clone(), body)),
+            );
+        }
+
+        let mut names = Names::new(self);
+        let mut start = self.start().clone();
+        if nullable.contains(&start) {
+            if mentions(self, &start) {
+                let old = std::mem::replace(&mut start, names.fresh(self.start()));
+                productions.push(Production::new(start.clone(), [Symbol::Nonterminal(old)]));
+            }
+            productions.push(Production::new(start.clone(), []));
+        }
+        Normalized {
+            grammar: Grammar::new(start, productions),
+            introduced: names.introduced,
+        }
+    }
+
+    /// Replaces every production whose body is a single nonterminal with the productions of
+    /// that nonterminal, following chains of them
+    pub fn remove_unit(&self) -> Self {
+        let unit = |production: &Production<T, N>| match &production.body[..] {
+            [Symbol::Nonterminal(n)] => Some(n.clone()),
+            _ => None,
+        };
+        let mut productions = Vec::new();
+        for head in self.nonterminals() {
+            let mut reached = HashSet::from([head.clone()]);
+            let mut pending = vec![head.clone()];
+            while let Some(nonterminal) = pending.pop() {
+                for production in self.productions_of(&nonterminal) {
+                    match unit(production) {
+                        Some(n) => {
+                            if reached.insert(n.clone()) {
+                                pending.push(n);
+                            }
+                        }
+                        None => {
+                            productions.push(Production::new(head.clone(), production.body.clone()))
+                        }
+                    }
+                }
+            }
+        }
+        Grammar::new(self.start().clone(), productions)
+    }
+
+    /// Converts the grammar to Chomsky normal form, where every body is a terminal or two
+    /// nonterminals other than the start symbol. The start symbol may also have an empty
+    /// production, if the language has the empty word.
+    ///
+    /// Introduces a new start symbol if the old one appears in some body, a nonterminal
+    /// for each terminal in a longer body, and a nonterminal for each extra symbol in
+    /// bodies longer than two.
+    pub fn chomsky(&self) -> Normalized<T, N> {
+        let mut names = Names::new(self);
+        let mut start = self.start().clone();
+        let mut productions = self.productions().to_vec();
+        if mentions(self, &start) {
+            let old = std::mem::replace(&mut start, names.fresh(self.start()));
+            productions.push(Production::new(start.clone(), [Symbol::Nonterminal(old)]));
+        }
+        // The start symbol is in no body, so no start symbol is introduced here
+        let grammar = Grammar::new(start, productions)
+            .remove_epsilon()
+            .grammar
+            .remove_unit()
+            .remove_useless();
+
+        let mut terminals: Vec<(T, N)> = Vec::new();
+        let mut productions = Vec::new();
+        for production in grammar.productions() {
+            if production.body.len() < 2 {
+                productions.push(production.clone());
+                continue;
+            }
+            let mut body = Vec::new();
+            for symbol in &production.body {
+                body.push(match symbol {
+                    Symbol::Nonterminal(n) => n.clone(),
+                    Symbol::Terminal(t) => match terminals.iter().find(|(u, _)| u == t) {
+                        Some((_, n)) => n.clone(),
+                        None => {
+                            let n = names.fresh(&production.head);
+                            productions
+                                .push(Production::new(n.clone(), [Symbol::Terminal(t.clone())]));
+                            terminals.push((t.clone(), n.clone()));
+                            n
+                        }
+                    },
+                });
+            }
+            let mut head = production.head.clone();
+            let (last, rest) = body.split_last().expect("body has two symbols");
+            let (second_last, rest) = rest.split_last().expect("body has two symbols");
+            for symbol in rest {
+                let next = names.fresh(&production.head);
+                productions.push(Production::new(
+                    head,
+                    [
+                        Symbol::Nonterminal(symbol.clone()),
+                        Symbol::Nonterminal(next.clone()),
+                    ],
+                ));
+                head = next;
+            }
+            productions.push(Production::new(
+                head,
+                [
+                    Symbol::Nonterminal(second_last.clone()),
+                    Symbol::Nonterminal(last.clone()),
+                ],
+            ));
+        }
+        Normalized {
+            grammar: Grammar::new(grammar.start().clone(), productions),
+            introduced: names.introduced,
+        }
+    }
+
+    /// Converts the grammar to Greibach normal form, where every body is a terminal
+    /// followed by nonterminals. The start symbol may also have an empty production, if
+    /// the language has the empty word.
+    ///
+    /// Goes through [`Grammar::chomsky`], whose introduced nonterminals are reported too,
+    /// and introduces a nonterminal for each left recursive one it removes.
+    pub fn greibach(&self) -> Normalized<T, N> {
+        let Normalized {
+            grammar,
+            introduced,
+        } = self.chomsky();
+        let mut names = Names::new(&grammar);
+        names.introduced = introduced;
+        let start = grammar.start().clone();
+        let epsilon = grammar.productions_of(&start).any(|p| p.body.is_empty());
+
+        let order = grammar.nonterminals().to_vec();
+        let leading = |body: &Body<T, N>| match body.first() {
+            Some(Symbol::Nonterminal(n)) => order.iter().position(|m| m == n),
+            _ => None,
+        };
+        let mut rules = order
+            .iter()
+            .map(|head| {
+                grammar
+                    .productions_of(head)
+                    .filter(|p| !p.body.is_empty())
+                    .map(|p| p.body.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Make every body of the i-th nonterminal start with a terminal or a later one
+        let mut tails = Vec::new();
+        for i in 0..order.len() {
+            for j in 0..i {
+                rules[i] = substitute(&rules[i], |body| {
+                    (leading(body) == Some(j)).then_some(&rules[j])
+                });
+            }
+            let (recursive, other): (Vec<_>, Vec<_>) = rules[i]
+                .iter()
+                .cloned()
+                .partition(|body| leading(body) == Some(i));
+            if recursive.is_empty() {
+                continue;
+            }
+            let tail = names.fresh(&order[i]);
+            let with_tail = |bodies: &[Body<T, N>]| {
+                let mut with_tail = bodies.to_vec();
+                with_tail.extend(bodies.iter().map(|body| {
+                    let mut body = body.clone();
+                    body.push(Symbol::Nonterminal(tail.clone()));
+                    body
+                }));
+                with_tail
+            };
+            let alphas = recursive
+                .into_iter()
+                .map(|body| body[1..].to_vec())
+                .collect::<Vec<_>>();
+            rules[i] = with_tail(&other);
+            tails.push((tail.clone(), with_tail(&alphas)));
+        }
+
+        // The last nonterminal already starts with terminals, and so does each one after
+        // substituting the later ones
+        for i in (0..order.len()).rev() {
+            let (done, later) = rules.split_at_mut(i + 1);
+            done[i] = substitute(&done[i], |body| leading(body).map(|k| &later[k - i - 1]));
+        }
+        for (_, bodies) in &mut tails {
+            *bodies = substitute(bodies, |body| leading(body).map(|k| &rules[k]));
+        }
+
+        let mut productions = order
+            .iter()
+            .zip(rules)
+            .chain(tails.iter().map(|(tail, bodies)| (tail, bodies.clone())))
+            .flat_map(|(head, bodies)| {
+                bodies
+                    .into_iter()
+                    .map(move |body| Production::new(head.clone(), body))
+            })
+            .collect::<Vec<_>>();
+        if epsilon {
+            productions.push(Production::new(start.clone(), []));
+        }
+        let grammar = Grammar::new(start, productions).remove_useless();
+        let introduced = names
+            .introduced
+            .into_iter()
+            .filter(|n| grammar.nonterminals().contains(n))
+            .collect();
+        Normalized {
+            grammar,
+            introduced,
+        }
+    }
+}
+
+impl<T, N> Grammar<T, N>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Hash + Eq,
+{
+    /// Builds an automaton for a grammar in Greibach normal form that consumes an input
+    /// symbol on every movement, or `None` if the grammar is not in that form.
+    ///
+    /// Like [`Grammar::automaton`] it has a single state and accepts by empty stack, but a
+    /// nonterminal on top of the stack is replaced by the rest of a production that starts
+    /// with the next input symbol. The only epsilon movement is the empty production of
+    /// the start symbol, if there is one.
+    pub fn real_time_automaton(&self) -> Option<GrammarAutomata<T, N>> {
+        let mut movements = Movements::new();
+        for production in self.productions() {
+            let v = match production.body.split_first() {
+                Some((Symbol::Terminal(t), rest))
+                    if rest.iter().all(|s| matches!(s, Symbol::Nonterminal(_))) =>
+                {
+                    Some(t.clone())
+                }
+                None if &production.head == self.start() => None,
+                _ => return None,
+            };
+            let push = production.body.iter().skip(1).cloned().collect();
+            movements.insert(
+                ((), v, Symbol::Nonterminal(production.head.clone())),
+                ((), push),
+            );
+        }
+        Some(AutomataBuilder::new(
+            (),
+            vec![Symbol::Nonterminal(self.start().clone())],
+            movements,
+        ))
+    }
+}
+
+/// Whether `nonterminal` appears in the body of some production
+fn mentions<T, N: Eq>(grammar: &Grammar<T, N>, nonterminal: &N) -> bool {
+    grammar
+        .productions()
+        .iter()
+        .flat_map(|p| &p.body)
+        .any(|symbol| matches!(symbol, Symbol::Nonterminal(n) if n == nonterminal))
+}
+
+/// Replaces the first symbol of each body by each of the bodies `replacement` gives for it
+fn substitute<'a, T, N, F>(bodies: &[Body<T, N>], replacement: F) -> Vec<Body<T, N>>
+where
+    T: Clone + Eq + 'a,
+    N: Clone + Eq + 'a,
+    F: Fn(&Body<T, N>) -> Option<&'a Vec<Body<T, N>>>,
+{
+    let mut substituted: Vec<Body<T, N>> = Vec::new();
+    for body in bodies {
+        let new = match replacement(body) {
+            Some(replacement) => replacement
+                .iter()
+                .map(|prefix| prefix.iter().chain(&body[1..]).cloned().collect())
+                .collect(),
+            None => vec![body.clone()],
+        };
+        for body in new {
+            if !substituted.contains(&body) {
+                substituted.push(body);
+            }
+        }
+    }
+    substituted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{
+        equivalence::compare_up_to,
+        fixtures::{expressions, parentheses},
+        grammar::{Grammar, Production, Symbol},
+        Budget,
+    };
+
+    use super::FreshNonterminal;
+
+    fn assert_same_language(left: &Grammar<char, char>, right: &Grammar<char, char>) {
+        let budget = Budget::unlimited().max_stack_depth(8);
+        let counterexample = compare_up_to(
+            &left.automaton().budget(budget),
+            &right.automaton().budget(budget),
+            left.terminals().iter().copied(),
+            4,
+        );
+        assert_eq!(counterexample.map(|c| c.word), None);
+    }
+
+    #[test]
+    fn test_fresh_nonterminals() {
+        let taken = HashSet::from(["S".to_string(), "S'".to_string()]);
+        assert_eq!(String::fresh(&"S".to_string(), &taken), "S''");
+        assert_eq!(char::fresh(&'S', &HashSet::from(['A', 'B'])), 'C');
+        assert_eq!(usize::fresh(&0, &HashSet::from([0, 3])), 4);
+    }
+
+    #[test]
+    fn test_remove_epsilon() {
+        let grammar = parentheses();
+        let normalized = grammar.remove_epsilon();
+        assert_eq!(normalized.introduced, ['A']);
+        assert_eq!(normalized.grammar.start(), &'A');
+        assert_eq!(
+            normalized
+                .grammar
+                .productions()
+                .iter()
+                .filter(|p| p.body.is_empty())
+                .collect::<Vec<_>>(),
+            [&Production::new('A', [])]
+        );
+        assert_same_language(&grammar, &normalized.grammar);
+    }
+
+    #[test]
+    fn test_remove_unit() {
+        let grammar = expressions();
+        let reduced = grammar.remove_unit();
+        assert!(reduced
+            .productions()
+            .iter()
+            .all(|p| !matches!(p.body[..], [Symbol::Nonterminal(_)])));
+        assert_eq!(reduced.productions_of(&'E').count(), 4);
+        assert_same_language(&grammar, &reduced);
+    }
+
+    #[test]
+    fn test_chomsky() {
+        for grammar in [expressions(), parentheses()] {
+            let normalized = grammar.chomsky();
+            let cnf = &normalized.grammar;
+            for production in cnf.productions() {
+                match production.body[..] {
+                    [Symbol::Terminal(_)] => {}
+                    [Symbol::Nonterminal(b), Symbol::Nonterminal(c)] => {
+                        assert_ne!(&b, cnf.start());
+                        assert_ne!(&c, cnf.start());
+                    }
+                    [] => assert_eq!(&production.head, cnf.start()),
+                    _ => panic!("{production:?} is not in Chomsky normal form"),
+                }
+            }
+            assert!(normalized
+                .introduced
+                .iter()
+                .all(|n| !grammar.nonterminals().contains(n)));
+            assert_same_language(&grammar, cnf);
+        }
+    }
+
+    #[test]
+    fn test_greibach() {
+        for grammar in [expressions(), parentheses()] {
+            let normalized = grammar.greibach();
+            let gnf = &normalized.grammar;
+            for production in gnf.productions() {
+                match production.body.split_first() {
+                    Some((Symbol::Terminal(_), rest)) => {
+                        assert!(rest.iter().all(|s| matches!(s, Symbol::Nonterminal(_))))
+                    }
+                    None => assert_eq!(&production.head, gnf.start()),
+                    _ => panic!("{production:?} is not in Greibach normal form"),
+                }
+            }
+            assert_same_language(&grammar, gnf);
+
+            let automata_builder = gnf.real_time_automaton().unwrap();
+            for word in ["n+n*(n)", "((n))", "(())()", "()"] {
+                assert_eq!(
+                    automata_builder.build(word.chars()).complete(),
+                    gnf.automaton().build(word.chars()).complete(),
+                    "{word}"
+                );
+            }
+        }
+        assert!(expressions().real_time_automaton().is_none());
+    }
+}
@@ -0,0 +1,100 @@
+//! Why a run did not accept its word
+
+use std::fmt::{self, Display};
+
+use crate::Configuration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RejectionReason<VocabElement, StackData> {
+    /// No movement reads the next symbol with this symbol on top of the stack
+    NoMovement {
+        symbol: VocabElement,
+        top: StackData,
+    },
+    /// The stack was emptied before the whole word was read
+    StackEmptied,
+    /// The whole word was read, but the stack is not empty
+    StackNotEmpty,
+    /// The whole word was read, but not in an accepting state
+    NotAccepting,
+    /// The run went back to a configuration it had already been in
+    Diverged,
+    /// The run did not finish within its [`crate::Budget`]
+    BudgetExceeded,
+}
+
+/// Whether a run accepted its word, and why not if it didn't
+pub type Outcome<VocabElement, StackData, Q> = Result<(), Rejection<VocabElement, StackData, Q>>;
+
+/// The reason a run rejected its word, with the configuration of the branch that got
+/// furthest into the word, where it got stuck
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection<VocabElement, StackData, Q> {
+    reason: RejectionReason<VocabElement, StackData>,
+    configuration: Configuration<StackData, Q>,
+}
+
+impl<VocabElement, StackData, Q> Rejection<VocabElement, StackData, Q> {
+    pub(crate) fn new(
+        reason: RejectionReason<VocabElement, StackData>,
+        configuration: Configuration<StackData, Q>,
+    ) -> Self {
+        Self {
+            reason,
+            configuration,
+        }
+    }
+
+    pub fn reason(&self) -> &RejectionReason<VocabElement, StackData> {
+        &self.reason
+    }
+
+    pub fn configuration(&self) -> &Configuration<StackData, Q> {
+        &self.configuration
+    }
+
+    /// Number of input symbols consumed when the run got stuck
+    pub fn position(&self) -> usize {
+        self.configuration.consumed()
+    }
+}
+
+impl<VocabElement, StackData, Q> Display for Rejection<VocabElement, StackData, Q>
+where
+    VocabElement: Display,
+    StackData: Display,
+    Q: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "after {} symbols, in state {} with stack ",
+            self.position(),
+            self.configuration.state()
+        )?;
+        if self.configuration.stack().is_empty() {
+            write!(f, "ε")?;
+        }
+        for symbol in self.configuration.stack().iter() {
+            write!(f, "{symbol}")?;
+        }
+        write!(f, ": ")?;
+        match &self.reason {
+            RejectionReason::NoMovement { symbol, top } => {
+                write!(
+                    f,
+                    "no movement reads {symbol} with {top} on top of the stack"
+                )
+            }
+            RejectionReason::StackEmptied => write!(f, "the stack is empty but input remains"),
+            RejectionReason::StackNotEmpty => {
+                write!(f, "the input is over but the stack is not empty")
+            }
+            RejectionReason::NotAccepting => {
+                write!(f, "the input is over but the state is not accepting")
+            }
+            RejectionReason::Diverged => write!(f, "the run loops forever"),
+            RejectionReason::BudgetExceeded => write!(f, "the run exceeded its budget"),
+        }
+    }
+}
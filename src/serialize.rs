@@ -0,0 +1,209 @@
+//! Serde support, behind the `serde` feature.
+//!
+//! [`Movements`] is represented as a list of transitions rather than as a map, since its
+//! keys are tuples that formats like JSON can't use as map keys:
+//!
+//! ```json
+//! [
+//!   { "from": "q0", "input": "a", "top": "Z", "to": "q0", "push": ["A", "Z"] },
+//!   { "from": "q1", "input": null, "top": "Z", "to": "q1", "push": [] }
+//! ]
+//! ```
+//!
+//! `input` may be left out for epsilon movements and `push` for movements that only pop.
+//! Transitions are written in ascending order, and so are the `accepting_states` of an
+//! [`crate::AutomataBuilder`], so the same automaton is always serialized the same way.
+//!
+//! Stacks are written in the order [`crate::Stack`] holds them: `initial_stack` lists the
+//! bottom of the stack first, while `push` lists the top first, like the vectors of
+//! [`Movements`]. `"initial_stack": ["Z", "A"]` starts with `A` on top of `Z`, and
+//! `"push": ["A", "Z"]` leaves the same stack when it replaces a `Z` at the bottom.
+
+use std::{collections::HashSet, hash::Hash};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Movements;
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct TransitionRef<'a, VocabElement, StackData, Q> {
+    from: &'a Q,
+    input: Option<&'a VocabElement>,
+    top: &'a StackData,
+    to: &'a Q,
+    push: &'a [StackData],
+}
+
+#[derive(Deserialize)]
+#[serde(bound(
+    deserialize = "VocabElement: Deserialize<'de>, StackData: Deserialize<'de>, Q: Deserialize<'de>"
+))]
+struct TransitionRecord<VocabElement, StackData, Q> {
+    from: Q,
+    #[serde(default)]
+    input: Option<VocabElement>,
+    top: StackData,
+    to: Q,
+    #[serde(default)]
+    push: Vec<StackData>,
+}
+
+impl<VocabElement, StackData, Q> Serialize for Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Serialize + Ord,
+    StackData: Serialize + Ord,
+    Q: Serialize + Ord,
+{
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: Serializer,
+    {
+        let mut transitions = self
+            .iter()
+            .map(|((from, input, top), (to, push))| TransitionRef {
+                from,
+                input: input.as_ref(),
+                top,
+                to,
+                push,
+            })
+            .collect::<Vec<_>>();
+        transitions.sort();
+        serializer.collect_seq(transitions)
+    }
+}
+
+/// Serializes a set in ascending order rather than in its iteration order
+pub(crate) fn sorted<T, Ser>(set: &HashSet<T>, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+where
+    T: Serialize + Ord,
+    Ser: Serializer,
+{
+    let mut elements = set.iter().collect::<Vec<_>>();
+    elements.sort();
+    serializer.collect_seq(elements)
+}
+
+impl<'de, VocabElement, StackData, Q> Deserialize<'de> for Movements<VocabElement, StackData, Q>
+where
+    VocabElement: Deserialize<'de> + Hash + Eq,
+    StackData: Deserialize<'de> + Hash + Eq,
+    Q: Deserialize<'de> + Hash + Eq,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let transitions =
+            Vec::<TransitionRecord<VocabElement, StackData, Q>>::deserialize(deserializer)?;
+        Ok(transitions
+            .into_iter()
+            .map(|t| ((t.from, t.input, t.top), (t.to, t.push)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::{fixtures::an_bn, Acceptance, AutomataBuilder, AutomataResult, Budget, Movements};
+
+    /// The [`an_bn`] movements with owned state names, so that they can be deserialized
+    fn owned_an_bn() -> Movements<char, char, String> {
+        an_bn()
+            .iter()
+            .map(|((from, v, top), (to, push))| {
+                ((from.to_string(), *v, *top), (to.to_string(), push.clone()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_movements() {
+        let movements = owned_an_bn();
+        let json = serde_json::to_string(&movements).unwrap();
+        let back: Movements<char, char, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, movements);
+
+        let keys = serde_json::to_value(&movements)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| (t["from"].clone(), t["input"].clone(), t["top"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                ("q0", Some("a"), "A"),
+                ("q0", Some("a"), "Z"),
+                ("q0", Some("b"), "A"),
+                ("q1", None, "Z"),
+                ("q1", Some("b"), "A"),
+            ]
+            .map(|(from, input, top)| (from.into(), input.into(), top.into()))
+        );
+
+        let back: Movements<char, char, String> =
+            serde_json::from_str(r#"[{ "from": "q1", "top": "Z", "to": "q2" }]"#).unwrap();
+        assert_eq!(
+            back.get(&("q1".to_string(), None, 'Z')),
+            Some(&HashSet::from([("q2".to_string(), vec![])]))
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let builder = AutomataBuilder::new("q0".to_string(), vec!['Z'], owned_an_bn())
+            .accepting(Acceptance::FinalState, ["q2", "q0", "q1"].map(String::from))
+            .budget(Budget::unlimited().max_steps(100));
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(json["initial_state"], "q0");
+        assert_eq!(json["initial_stack"], serde_json::json!(["Z"]));
+        assert_eq!(
+            json["accepting_states"],
+            serde_json::json!(["q0", "q1", "q2"])
+        );
+
+        let back: AutomataBuilder<char, String, Movements<char, char, String>> =
+            serde_json::from_value(json).unwrap();
+        assert_eq!(back.initial_state(), builder.initial_state());
+        assert_eq!(back.initial_stack(), builder.initial_stack());
+        assert_eq!(back.movements(), builder.movements());
+        assert_eq!(back.acceptance(), Acceptance::FinalState);
+        assert_eq!(back.accepting_states(), builder.accepting_states());
+        assert_eq!(back.budget, builder.budget);
+        assert!(back.build("aabb".chars()).complete());
+
+        let back: AutomataBuilder<char, String, Movements<char, char, String>> =
+            serde_json::from_str(
+                r#"{ "initial_state": "q0", "initial_stack": ["Z"], "movements": [] }"#,
+            )
+            .unwrap();
+        assert_eq!(back.acceptance(), Acceptance::EmptyStack);
+        assert!(back.accepting_states().is_empty());
+        assert_eq!(back.budget, Budget::default());
+    }
+
+    #[test]
+    fn test_result() {
+        for result in [
+            AutomataResult::Accept,
+            AutomataResult::Processing,
+            AutomataResult::NotAccepting,
+            AutomataResult::Diverged,
+            AutomataResult::BudgetExceeded,
+        ] {
+            let json = serde_json::to_string(&result).unwrap();
+            assert_eq!(
+                serde_json::from_str::<AutomataResult>(&json).unwrap(),
+                result
+            );
+        }
+        assert_eq!(
+            serde_json::to_string(&AutomataResult::Accept).unwrap(),
+            r#""Accept""#
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Execution traces of a run, printable in the `(q, w, γ) ⊢ (q', w', γ')` notation
+
+use std::fmt::{self, Display};
+
+use crate::{AutomataResult, Configuration, Stack, Transition};
+
+/// A configuration along a traced branch, with the movement that led to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step<VocabElement, StackData, Q> {
+    configuration: Configuration<StackData, Q>,
+    movement: Option<Transition<VocabElement, StackData, Q>>,
+}
+
+impl<VocabElement, StackData, Q> Step<VocabElement, StackData, Q> {
+    pub(crate) fn new(
+        configuration: Configuration<StackData, Q>,
+        movement: Option<Transition<VocabElement, StackData, Q>>,
+    ) -> Self {
+        Self {
+            configuration,
+            movement,
+        }
+    }
+
+    pub fn configuration(&self) -> &Configuration<StackData, Q> {
+        &self.configuration
+    }
+
+    pub fn state(&self) -> &Q {
+        &self.configuration.state
+    }
+
+    pub fn stack(&self) -> &Stack<StackData> {
+        &self.configuration.stack
+    }
+
+    /// Number of input symbols consumed so far
+    pub fn consumed(&self) -> usize {
+        self.configuration.consumed
+    }
+
+    /// The movement that fired to reach this configuration, `None` for the initial one
+    pub fn movement(&self) -> Option<&Transition<VocabElement, StackData, Q>> {
+        self.movement.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace<VocabElement, StackData, Q> {
+    input: Vec<VocabElement>,
+    steps: Vec<Step<VocabElement, StackData, Q>>,
+    result: AutomataResult,
+}
+
+impl<VocabElement, StackData, Q> Trace<VocabElement, StackData, Q> {
+    pub(crate) fn new(
+        input: Vec<VocabElement>,
+        steps: Vec<Step<VocabElement, StackData, Q>>,
+        result: AutomataResult,
+    ) -> Self {
+        Self {
+            input,
+            steps,
+            result,
+        }
+    }
+
+    /// The whole word the automaton was run on
+    pub fn input(&self) -> &[VocabElement] {
+        &self.input
+    }
+
+    /// The configurations along the branch, starting with the initial one
+    pub fn steps(&self) -> &[Step<VocabElement, StackData, Q>] {
+        &self.steps
+    }
+
+    pub fn result(&self) -> AutomataResult {
+        self.result
+    }
+
+    pub fn accepted(&self) -> bool {
+        self.result == AutomataResult::Accept
+    }
+}
+
+impl<VocabElement, StackData, Q> Display for Trace<VocabElement, StackData, Q>
+where
+    VocabElement: Display,
+    StackData: Display,
+    Q: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n⊢ ")?;
+            }
+            write!(f, "({}, ", step.state())?;
+            write_symbols(f, &self.input[step.consumed()..])?;
+            write!(f, ", ")?;
+            write_symbols(f, step.stack().iter())?;
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_symbols<I>(f: &mut fmt::Formatter<'_>, symbols: I) -> fmt::Result
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let mut empty = true;
+    for symbol in symbols {
+        write!(f, "{symbol}")?;
+        empty = false;
+    }
+    if empty {
+        write!(f, "ε")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fixtures, AutomataBuilder, AutomataResult, Movements, Stack};
+
+    fn an_bn() -> AutomataBuilder<char, &'static str, Movements<char, char, &'static str>> {
+        AutomataBuilder::new("q0", vec!['Z'], fixtures::an_bn())
+    }
+
+    #[test]
+    fn test_accepting_trace() {
+        let trace = an_bn().build("aabb".chars()).trace();
+        assert!(trace.accepted());
+        assert_eq!(trace.steps().len(), 6);
+        assert_eq!(trace.steps()[0].movement(), None);
+        assert_eq!(
+            trace.steps()[1].movement(),
+            Some(&(("q0", Some('a'), 'Z'), ("q0", vec!['A', 'Z'])))
+        );
+        assert_eq!(
+            trace.to_string(),
+            "(q0, aabb, Z)\n\
+             ⊢ (q0, abb, AZ)\n\
+             ⊢ (q0, bb, AAZ)\n\
+             ⊢ (q1, b, AZ)\n\
+             ⊢ (q1, ε, Z)\n\
+             ⊢ (q1, ε, ε)"
+        );
+    }
+
+    #[test]
+    fn test_rejecting_trace() {
+        let trace = an_bn().build("abab".chars()).trace();
+        assert_eq!(trace.result(), AutomataResult::NotAccepting);
+        assert_eq!(trace.input(), ['a', 'b', 'a', 'b']);
+        let last = trace.steps().last().unwrap();
+        assert_eq!(last.consumed(), 2);
+        assert_eq!(last.state(), &"q1");
+        assert_eq!(last.stack(), &Stack::new(vec![]));
+        assert_eq!(
+            trace.to_string(),
+            "(q0, abab, Z)\n⊢ (q0, bab, AZ)\n⊢ (q1, ab, Z)\n⊢ (q1, ab, ε)"
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Enumeration of words in length-lexicographic (shortlex) order
+
+use std::hash::Hash;
+
+use crate::{AutomataBuilder, Movement};
+
+/// Every word over an alphabet, shortest first and then in the order of the alphabet
+#[derive(Debug, Clone)]
+pub struct Shortlex<VocabElement> {
+    alphabet: Vec<VocabElement>,
+    /// Position in the alphabet of each symbol of the next word
+    next: Option<Vec<usize>>,
+    max_length: Option<usize>,
+}
+
+impl<VocabElement> Shortlex<VocabElement> {
+    /// Starts at the empty word. Repeated symbols in `alphabet` yield repeated words.
+    pub fn new<A>(alphabet: A) -> Self
+    where
+        A: IntoIterator<Item = VocabElement>,
+    {
+        Self {
+            alphabet: alphabet.into_iter().collect(),
+            next: Some(vec![]),
+            max_length: None,
+        }
+    }
+
+    /// Stops after the words of length `max_length`
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+}
+
+impl<VocabElement: Clone> Iterator for Shortlex<VocabElement> {
+    type Item = Vec<VocabElement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.next.take()?;
+        if self.max_length.is_some_and(|max| indices.len() > max) {
+            return None;
+        }
+        let word = indices.iter().map(|&i| self.alphabet[i].clone()).collect();
+
+        let mut next = indices;
+        match next.iter().rposition(|&i| i + 1 < self.alphabet.len()) {
+            Some(position) => {
+                next[position] += 1;
+                next[position + 1..].fill(0);
+            }
+            None if self.alphabet.is_empty() => return Some(word),
+            None => next = vec![0; next.len() + 1],
+        }
+        self.next = Some(next);
+        Some(word)
+    }
+}
+
+/// The words over `alphabet` that `builder` accepts, in shortlex order.
+///
+/// Every word is run separately, so automata that may diverge need a [`crate::Budget`].
+pub fn accepted_words<VocabElement, StackData, Q, M, A>(
+    builder: &AutomataBuilder<StackData, Q, M>,
+    alphabet: A,
+) -> AcceptedWords<'_, VocabElement, StackData, Q, M>
+where
+    A: IntoIterator<Item = VocabElement>,
+{
+    AcceptedWords {
+        builder,
+        words: Shortlex::new(alphabet),
+        max_count: None,
+    }
+}
+
+/// Iterator returned by [`accepted_words`].
+///
+/// Without a maximum length it never ends once the accepted words run out, even with a
+/// maximum count, as it keeps running longer and longer words.
+#[derive(Debug, Clone)]
+pub struct AcceptedWords<'a, VocabElement, StackData, Q, M> {
+    builder: &'a AutomataBuilder<StackData, Q, M>,
+    words: Shortlex<VocabElement>,
+    max_count: Option<usize>,
+}
+
+impl<VocabElement, StackData, Q, M> AcceptedWords<'_, VocabElement, StackData, Q, M> {
+    /// Only runs words of up to `max_length` symbols
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.words = self.words.max_length(max_length);
+        self
+    }
+
+    /// Stops after `max_count` accepted words
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+}
+
+impl<VocabElement, StackData, Q, M> Iterator for AcceptedWords<'_, VocabElement, StackData, Q, M>
+where
+    VocabElement: Clone,
+    StackData: Clone + Hash + Eq,
+    Q: Clone + Hash + Eq,
+    M: Clone + Movement<VocabElement, StackData, Q>,
+{
+    type Item = Vec<VocabElement>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.max_count == Some(0) {
+            return None;
+        }
+        let builder = self.builder;
+        let word = self
+            .words
+            .find(|word| builder.build(word.iter().cloned()).complete())?;
+        self.max_count = self.max_count.map(|count| count - 1);
+        Some(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fixtures::an_bn, AutomataBuilder};
+
+    use super::{accepted_words, Shortlex};
+
+    #[test]
+    fn test_shortlex() {
+        let words = Shortlex::new(['a', 'b'])
+            .max_length(2)
+            .map(String::from_iter)
+            .collect::<Vec<_>>();
+        assert_eq!(words, ["", "a", "b", "aa", "ab", "ba", "bb"]);
+        assert_eq!(Shortlex::<char>::new([]).count(), 1);
+        assert_eq!(Shortlex::new(['a', 'b', 'c']).max_length(3).count(), 40);
+    }
+
+    #[test]
+    /// Test for (a^n)(b^n) where n >= 1
+    fn test_accepted_words() {
+        let automata_builder = AutomataBuilder::new("q0", vec!['Z'], an_bn());
+
+        let words = accepted_words(&automata_builder, ['a', 'b'])
+            .max_length(6)
+            .map(String::from_iter)
+            .collect::<Vec<_>>();
+        assert_eq!(words, ["ab", "aabb", "aaabbb"]);
+        let words = accepted_words(&automata_builder, ['a', 'b'])
+            .max_count(4)
+            .map(String::from_iter)
+            .collect::<Vec<_>>();
+        assert_eq!(words, ["ab", "aabb", "aaabbb", "aaaabbbb"]);
+        let words = accepted_words(&automata_builder, ['a', 'b'])
+            .max_length(6)
+            .max_count(2)
+            .map(String::from_iter)
+            .collect::<Vec<_>>();
+        assert_eq!(words, ["ab", "aabb"]);
+        assert_eq!(
+            accepted_words(&automata_builder, ['a', 'b'])
+                .max_length(6)
+                .max_count(5)
+                .count(),
+            3
+        );
+    }
+}
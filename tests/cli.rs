@@ -0,0 +1,141 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+const AN_BN: &str = "\
+# a^n b^n, n >= 1
+initial: q0
+stack: Z
+q0, a, Z -> q0, A Z
+q0, a, A -> q0, A A
+q0, b, A -> q1, ε
+q1, b, A -> q1, ε
+q1, ε, Z -> q1, ε
+";
+
+/// Writes `text` to a definition file unique to the test `name`
+fn definition(name: &str, text: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("stack_automata-{}-{name}.txt", std::process::id()));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+fn run(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_stack_automata"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    input.write_all(stdin.unwrap_or("").as_bytes()).unwrap();
+    drop(input);
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn test_accept() {
+    let path = definition("accept", AN_BN);
+    let output = run(&[path.to_str().unwrap(), "ab", "aabb"], None);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "accept ab\naccept aabb\n");
+}
+
+#[test]
+fn test_reject() {
+    let path = definition("reject", AN_BN);
+    let output = run(&[path.to_str().unwrap(), "ab", "aab"], None);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "accept ab\nreject aab: after 3 symbols, in state q1 with stack AZ: \
+         the input is over but the stack is not empty\n"
+    );
+}
+
+#[test]
+fn test_stdin() {
+    let path = definition("stdin", AN_BN);
+    let output = run(&[path.to_str().unwrap()], Some("ab\n\naabb\n"));
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = stdout(&output);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "accept ab");
+    assert!(lines[1].starts_with("reject ε: "));
+    assert_eq!(lines[2], "accept aabb");
+}
+
+#[test]
+fn test_trace() {
+    let path = definition("trace", AN_BN);
+    let output = run(&["--trace", path.to_str().unwrap(), "ab"], None);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        "accept ab\n(q0, ab, Z)\n⊢ (q0, b, AZ)\n⊢ (q1, ε, Z)\n⊢ (q1, ε, ε)\n\n"
+    );
+}
+
+#[test]
+fn test_budget() {
+    let path = definition("budget", AN_BN);
+    let path = path.to_str().unwrap();
+    for args in [["--max-steps", "1"], ["--max-depth", "1"]] {
+        let output = run(&[args[0], args[1], path, "ab"], None);
+        assert_eq!(output.status.code(), Some(1));
+        let stdout = stdout(&output);
+        assert!(stdout.starts_with("reject ab: "), "{stdout}");
+        assert!(
+            stdout.ends_with("the run exceeded its budget\n"),
+            "{stdout}"
+        );
+    }
+
+    let output = run(&["--max-steps", "many", path, "ab"], None);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("--max-steps expects a number\nusage: "));
+}
+
+#[test]
+fn test_end_of_options() {
+    let path = definition("end_of_options", AN_BN);
+    let output = run(&["--", path.to_str().unwrap(), "--trace"], None);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = stdout(&output);
+    assert!(stdout.starts_with("reject --trace: "), "{stdout}");
+    assert_eq!(stdout.lines().count(), 1);
+}
+
+#[test]
+fn test_errors() {
+    let output = run(&[], None);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("missing definition file\nusage: "));
+
+    let output = run(&["--help"], None);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("usage: "));
+
+    let path = env::temp_dir().join("stack_automata-missing.txt");
+    let output = run(&[path.to_str().unwrap(), "ab"], None);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stdout(&output).is_empty());
+
+    let path = definition("errors", "initial: q0\nq0, a -> q0\n");
+    let output = run(&[path.to_str().unwrap(), "ab"], None);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("line 2"), "{}", stderr(&output));
+}